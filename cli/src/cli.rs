@@ -10,6 +10,7 @@ const VERSION: &str = env!("CARGO_PKG_VERSION");
     long_about = "Composer is a cli tool that empower streamlined cross-platform workflow creation, effortlessly translating configurable files into efficient WebAssembly (Wasm) format for enhanced development and operational efficiency."
 )]
 #[command(disable_version_flag = true)]
+#[allow(clippy::manual_non_exhaustive)]
 pub struct Cli {
     #[arg(
         short,
@@ -1,21 +1,96 @@
 use clap::Parser;
+use composer_primitives::Exception;
+use echo_library::{BuildOptions, Profile, Target};
+use std::str::FromStr;
+
+use crate::errors::IOError;
 
 use super::*;
 
 /// Compile and build program command.
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Build {
     #[arg(
         short,
         long,
-        help = "Optional Rust package generation path is created solely in case of build failure"
+        help = "Optional path where the Rust packages of the workflows are generated, they are kept in case of build failure"
     )]
     pub build_dir: Option<PathBuf>,
 
+    #[arg(
+        long,
+        help = "Keep the generated Rust packages after a successful build"
+    )]
+    pub keep_build: bool,
+
+    #[arg(
+        long,
+        env = "COMPOSER_CACHE_DIR",
+        help = "Optional path of the cache shared by the builds, defaults to ~/.cache/composer"
+    )]
+    pub cache_dir: Option<PathBuf>,
+
     #[arg(short, long, help = "Optional path to output workflow wasm")]
     pub output: Option<PathBuf>,
 
-    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory 
+    #[arg(
+        short,
+        long,
+        value_parser = clap::value_parser!(u16).range(1..),
+        help = "Number of workflows built at the same time, defaults to the number of CPUs"
+    )]
+    pub jobs: Option<u16>,
+
+    #[arg(
+        long,
+        help = "Keep building the remaining workflows after a workflow fails"
+    )]
+    pub keep_going: bool,
+
+    #[arg(long, help = "Build without accessing the network")]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        help = "Optional path of the vendored dependencies, defaults to the vendor directory of the package if any"
+    )]
+    pub vendor_dir: Option<PathBuf>,
+
+    #[arg(
+        long = "patch",
+        value_name = "NAME=PATH",
+        value_parser = parse_patch,
+        help = "Override a dependency of the generated packages with a local copy, can be repeated"
+    )]
+    pub patches: Vec<(String, PathBuf)>,
+
+    #[arg(
+        long,
+        help = "Platform the workflows are compiled for: wasm32-wasip1, wasm32-unknown-unknown or native, defaults to wasm32-wasip1"
+    )]
+    pub target: Option<Target>,
+
+    #[arg(
+        long,
+        help = "Cargo profile the workflows are compiled with: debug or release, defaults to release"
+    )]
+    pub profile: Option<Profile>,
+
+    #[arg(
+        long,
+        help = "Compile the wasm for small code size (opt-level \"z\") and shrink it with wasm-opt"
+    )]
+    pub optimize_size: bool,
+
+    #[arg(
+        long,
+        env = "COMPOSER_ENV",
+        help = "Optional environment of the manifest whose build settings are used"
+    )]
+    pub env: Option<String>,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
     pub source: Option<PathBuf>,
 }
 
@@ -25,9 +100,43 @@ impl Execute<Context> for Build {
 
     fn execute(self, mut context: Context) -> Result<Self::Output> {
         let start = Instant::now();
-        context.init(self.source, self.build_dir, self.output)?;
+        context.init(
+            self.source,
+            self.build_dir,
+            self.output,
+            self.env.as_deref(),
+        )?;
         context.parse()?;
-        context.build()?;
+
+        let settings = context.settings.clone();
+
+        context.build(BuildOptions {
+            verbose: context.quiet,
+            keep_build: self.keep_build,
+            cache_dir: self
+                .cache_dir
+                .or(settings.cache_dir)
+                .unwrap_or_else(BuildOptions::default_cache_dir),
+            jobs: self.jobs.or(settings.jobs).map_or(0, usize::from),
+            keep_going: self.keep_going,
+            offline: self.offline || settings.offline.unwrap_or_default(),
+            vendor_dir: self.vendor_dir.map(absolute_path).transpose()?,
+            patches: self
+                .patches
+                .into_iter()
+                .map(|(name, path)| Ok((name, absolute_path(path)?)))
+                .collect::<Result<_>>()?,
+            target: match self.target {
+                Some(target) => target,
+                None => parse_setting(settings.target.as_deref())?.unwrap_or_default(),
+            },
+            profile: match self.profile {
+                Some(profile) => profile,
+                None => parse_setting(settings.profile.as_deref())?.unwrap_or_default(),
+            },
+            optimize_size: self.optimize_size || settings.optimize_size.unwrap_or_default(),
+            lock_file: None,
+        })?;
         let end = Instant::now();
         let duration = end.duration_since(start);
         if context.quiet {
@@ -39,3 +148,25 @@ impl Execute<Context> for Build {
         Ok(())
     }
 }
+
+fn parse_setting<T: FromStr<Err = String>>(value: Option<&str>) -> Result<Option<T>> {
+    value
+        .map(str::parse)
+        .transpose()
+        .map_err(|err| Box::new(IOError::Other(err)) as Box<dyn Exception>)
+}
+
+fn parse_patch(value: &str) -> std::result::Result<(String, PathBuf), String> {
+    match value.split_once('=') {
+        Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+            Ok((name.to_string(), PathBuf::from(path)))
+        }
+        _ => Err("expected NAME=PATH".to_string()),
+    }
+}
+
+/// The generated packages are built outside of the current directory, so the paths given
+/// on the command line are made absolute
+fn absolute_path(path: PathBuf) -> Result<PathBuf> {
+    fs::canonicalize(&path).map_err(io_error)
+}
@@ -1,8 +1,11 @@
 use clap::Parser;
+use composer_primitives::constant::MANIFEST_FILE;
+use composer_primitives::Manifest;
 
 use super::*;
 
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Create {
     pub package_name: String,
 }
@@ -37,6 +40,35 @@ workflows(
         );
 
         fs::write(temp_path, content.as_bytes()).map_err(io_error)?;
+
+        // The settings left to their default are listed commented out, as a reference
+        let manifest = format!(
+            "\
+{}# output = \"build\"
+# target = \"wasm32-wasip1\"
+# profile = \"release\"
+# optimize_size = false
+
+# [dependencies]
+# shared = {{ path = \"../shared\" }}
+
+# [lints]
+# unused_task = \"deny\"
+# duplicate_action = \"allow\"
+
+# [kinds]
+# hello_world = \"../hello_world_macro\"
+
+# [patch]
+# openwhisk-rust = \"../openwhisk-rust\"
+
+# [env.dev]
+# profile = \"debug\"
+",
+            Manifest::new(&self.package_name)
+        );
+
+        fs::write(package.join(MANIFEST_FILE), manifest.as_bytes()).map_err(io_error)?;
         println!(
             "   \x1B[32m\x1b[1mCreated\x1b[0m: Workflow Package \x1B[34m\x1b[1m'{}' \x1b[0m",
             &self.package_name
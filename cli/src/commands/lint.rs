@@ -0,0 +1,30 @@
+use super::*;
+
+/// Check the config files against the lint rules command.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Lint {
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Execute<Context> for Lint {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        context.init(self.source, None, None, None)?;
+        context.parse()?;
+
+        let warnings = context.lint()?;
+
+        if context.quiet {
+            println!(
+                "   \x1B[32m\x1b[1mLinted\x1b[0m: {} warnings found in the workflow package",
+                warnings
+            );
+        }
+
+        Ok(())
+    }
+}
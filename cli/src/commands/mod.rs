@@ -1,8 +1,10 @@
 mod build;
 mod create;
+mod lint;
+mod update;
 mod validate;
 
-use self::{create::Create, validate::Validate};
+use self::{create::Create, lint::Lint, update::Update, validate::Validate};
 use crate::errors::io_error;
 use crate::types::Context;
 use build::Build;
@@ -27,6 +29,18 @@ pub enum Commands {
         command: Create,
     },
 
+    #[command(about = "Check the configuration files against the lint rules")]
+    Lint {
+        #[command(flatten)]
+        command: Lint,
+    },
+
+    #[command(about = "Resolve the dependencies of the workflows again and update composer.lock")]
+    Update {
+        #[command(flatten)]
+        command: Update,
+    },
+
     #[structopt(about = "Validate the configuration file")]
     Validate {
         #[command(flatten)]
@@ -0,0 +1,47 @@
+use clap::Parser;
+use echo_library::BuildOptions;
+
+use super::*;
+
+/// Refresh the lock file of the package command.
+#[derive(Parser, Debug)]
+#[group(skip)]
+pub struct Update {
+    #[arg(
+        short,
+        long,
+        help = "Optional path where the Rust packages of the workflows are generated"
+    )]
+    pub build_dir: Option<PathBuf>,
+
+    #[arg(long, help = "Resolve the dependencies without accessing the network")]
+    pub offline: bool,
+
+    #[arg(
+        long,
+        env = "COMPOSER_ENV",
+        help = "Optional environment of the manifest whose build settings are used"
+    )]
+    pub env: Option<String>,
+
+    /// The path(relative path or absolute path) of the directory, where the package is located. This is an optional path if you are in the current directory
+    pub source: Option<PathBuf>,
+}
+
+impl Execute<Context> for Update {
+    type Input = ();
+    type Output = ();
+
+    fn execute(self, mut context: Context) -> Result<Self::Output> {
+        context.init(self.source, self.build_dir, None, self.env.as_deref())?;
+        context.parse()?;
+
+        let offline = self.offline || context.settings.offline.unwrap_or_default();
+
+        context.update(BuildOptions {
+            verbose: context.quiet,
+            offline,
+            ..Default::default()
+        })
+    }
+}
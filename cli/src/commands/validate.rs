@@ -1,6 +1,7 @@
 use super::*;
 /// Compile the config file.
 #[derive(Parser, Debug)]
+#[group(skip)]
 pub struct Validate {
 
     /// The path(relative path or absolute path) of the directory, where the package is located. 
@@ -13,8 +14,9 @@ impl Execute<Context> for Validate {
 
     fn execute(self, mut context: Context) -> Result<Self::Output> {
         let start = Instant::now();
-        context.init(self.source, None, None)?;
+        context.init(self.source, None, None, None)?;
         context.parse()?;
+        context.lint()?;
         let end = Instant::now();
 
         let duration = end.duration_since(start);
@@ -60,6 +60,8 @@ pub fn run_with_args(cli: Cli) -> Result<()> {
     match cli.command {
         Commands::Build { command } => command.execute(context)?,
         Commands::Create { command } => command.execute()?,
+        Commands::Lint { command } => command.execute(context)?,
+        Commands::Update { command } => command.execute(context)?,
         Commands::Validate { command } => command.execute(context)?,
     };
 
@@ -1,23 +1,22 @@
 use super::*;
+use std::collections::btree_map::Entry;
 
+/// A package of the workspace, or the package itself outside of a workspace
+struct Member {
+    source_files: SourceFiles,
+    parser: Box<dyn Parser>,
+}
+
+#[derive(Default)]
 pub(crate) struct Context {
     build_directory: Option<BuildDirectory>,
     pub output_directory: Option<OutputDirectory>,
     source_files: Option<SourceFiles>,
-    parser: Box<dyn Parser>,
+    members: Vec<Member>,
     pub quiet: bool,
-}
-
-impl Default for Context {
-    fn default() -> Self {
-        Context {
-            build_directory: None,
-            output_directory: None,
-            source_files: None,
-            parser: Box::<Composer>::default(),
-            quiet: false,
-        }
-    }
+    /// Build settings of the manifest of the package, with the paths made relative to the
+    /// current directory
+    pub settings: BuildSettings,
 }
 
 impl Context {
@@ -29,20 +28,69 @@ impl Context {
         self.quiet = true;
     }
 
+    /// Reads the package and its manifest, the settings of the given environment of the
+    /// manifest are used where the command line leaves them unset.
     pub fn init(
         &mut self,
         source: Option<PathBuf>,
         build_directory: Option<PathBuf>,
         output_directory: Option<PathBuf>,
+        env: Option<&str>,
     ) -> Result<()> {
         self.build_directory = Some(
             BuildDirectory::new(build_directory)
                 .map_err(|x| Box::new(IOError::Anyhow(x)) as Box<dyn Exception>)?,
         );
-        self.source_files = Some(
-            SourceFiles::new(source)
+        let source_files = SourceFiles::new(source)
+            .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?;
+
+        self.settings = match (source_files.manifest(), env) {
+            (Some(manifest), env) => manifest
+                .settings(env)
                 .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?,
-        );
+            (None, Some(env)) => {
+                return Err(Box::new(IOError::Other(format!(
+                    "Environment {} is selected but the package has no {}",
+                    env, MANIFEST_FILE
+                ))))
+            }
+            (None, None) => BuildSettings::default(),
+        };
+
+        let base = source_files.base();
+        for path in [
+            &mut self.settings.output,
+            &mut self.settings.cache_dir,
+            &mut self.settings.vendor_dir,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = base.join(&path);
+        }
+
+        let output_directory = match (output_directory, &self.settings.output) {
+            (Some(output_directory), _) => Some(output_directory),
+            (None, Some(output)) => {
+                std::fs::create_dir_all(output).map_err(io_error)?;
+                Some(output.clone())
+            }
+            (None, None) => None,
+        };
+
+        let members = source_files
+            .members()
+            .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?
+            .unwrap_or_else(|| vec![source_files.clone()]);
+
+        self.members = members
+            .into_iter()
+            .map(|source_files| Member {
+                source_files,
+                parser: Box::<Composer>::default(),
+            })
+            .collect();
+        self.source_files = Some(source_files);
         self.output_directory = Some(
             OutputDirectory::new(output_directory)
                 .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?,
@@ -52,25 +100,267 @@ impl Context {
     }
 
     pub fn parse(&self) -> Result<()> {
-        self.parser
-            .parse(self.source_files.as_ref().ok_or_else(|| {
-                Box::new(IOError::Other("Build file is not initialised".to_string()))
-                    as Box<dyn Exception>
-            })?)?;
+        if self.members.is_empty() {
+            return Err(Box::new(IOError::Other(
+                "Build file is not initialised".to_string(),
+            )));
+        }
+
+        for member in self.members.iter() {
+            let result = member.parser.parse(&member.source_files);
+
+            if self.is_workspace() {
+                result.map_err(|err| {
+                    Box::new(IOError::Other(format!(
+                        "{}: {}",
+                        member.source_files.name(),
+                        err
+                    ))) as Box<dyn Exception>
+                })?;
+            } else {
+                result?;
+            }
+        }
+
         Ok(())
     }
 
-    pub fn build(&self) -> Result<()> {
-        self.parser.build(
-            self.build_directory.as_ref().ok_or_else(|| {
+    /// Builds the parsed workflows. The generated packages are kept when the build fails, or
+    /// always when `keep_build` is set, and the path of the build directory is printed.
+    pub fn build(&mut self, options: BuildOptions) -> Result<()> {
+        let result = self.for_each_member(
+            &options,
+            |member, build_directory, output_directory, options| {
+                member
+                    .parser
+                    .build(build_directory, output_directory, options)
+            },
+        );
+
+        self.keep_build_directory(result.is_err() || options.keep_build)?;
+
+        result
+    }
+
+    /// Resolves the dependencies of the generated packages again and rewrites the lock file
+    /// of the package
+    pub fn update(&mut self, options: BuildOptions) -> Result<()> {
+        let result = self.for_each_member(&options, |member, build_directory, _, options| {
+            member.parser.update(build_directory, options)
+        });
+
+        self.keep_build_directory(result.is_err() || options.keep_build)?;
+
+        result
+    }
+
+    /// Checks the parsed config files against the lint rules and prints the findings, the
+    /// severities of the manifest of a member taking precedence over those of the workspace.
+    /// The warnings are not printed with `--quiet`, the denied findings always are
+    ///
+    /// Returns the number of warnings, or an error if a rule denied by the manifest is broken
+    pub fn lint(&self) -> Result<usize> {
+        let mut warnings = 0;
+        let mut errors = 0;
+
+        for member in self.members.iter() {
+            let mut severities = BTreeMap::new();
+
+            for source_files in [self.source_files.as_ref(), Some(&member.source_files)]
+                .into_iter()
+                .flatten()
+            {
+                if let Some(manifest) = source_files.manifest() {
+                    severities.extend(manifest.lints.clone());
+                }
+            }
+
+            for lint in member.parser.lint(&severities)? {
+                match lint.severity {
+                    Severity::Deny => errors += 1,
+                    _ => warnings += 1,
+                }
+
+                if lint.severity != Severity::Deny && !self.quiet {
+                    continue;
+                }
+
+                let color = match lint.severity {
+                    Severity::Deny => "31",
+                    _ => "33",
+                };
+
+                let message = match self.is_workspace() {
+                    true => format!("{}: {}", member.source_files.name(), lint.message),
+                    false => lint.message.clone(),
+                };
+
+                eprintln!(
+                    "   \x1B[{color}m\x1b[1m{}[{}]\x1b[0m: {}",
+                    lint.severity,
+                    lint.rule,
+                    with_span(message, lint.span.as_ref())
+                );
+            }
+        }
+
+        if errors > 0 {
+            return Err(Box::new(IOError::Other(format!(
+                "{} lint findings denied by the manifest, {} warnings",
+                errors, warnings
+            ))));
+        }
+
+        Ok(warnings)
+    }
+
+    fn is_workspace(&self) -> bool {
+        self.source_files
+            .as_ref()
+            .and_then(|source_files| source_files.manifest())
+            .is_some_and(|manifest| manifest.workspace.is_some())
+    }
+
+    /// Runs the given step for the package, or for every member of the workspace, each member
+    /// having its own build and output directories named after it
+    fn for_each_member<F>(&self, options: &BuildOptions, step: F) -> Result<()>
+    where
+        F: Fn(&Member, &BuildDirectory, &OutputDirectory, &BuildOptions) -> Result<()>,
+    {
+        let build_directory = self.build_directory.as_ref().ok_or_else(|| {
+            Box::new(IOError::Other("Build file is not initialised".to_string()))
+                as Box<dyn Exception>
+        })?;
+        let output_directory = self.output_directory.as_ref().ok_or_else(|| {
+            Box::new(IOError::Other("output file is not initialised".to_string()))
+                as Box<dyn Exception>
+        })?;
+
+        if !self.is_workspace() {
+            let member = self.members.first().ok_or_else(|| {
                 Box::new(IOError::Other("Build file is not initialised".to_string()))
                     as Box<dyn Exception>
-            })?,
-            self.output_directory.as_ref().ok_or_else(|| {
-                Box::new(IOError::Other("output file is not initialised".to_string()))
-                    as Box<dyn Exception>
-            })?,
-            self.quiet,
-        )
+            })?;
+            let options = self.package_options(&member.source_files, options.clone())?;
+
+            return step(member, build_directory, output_directory, &options);
+        }
+
+        let mut errors = Vec::new();
+
+        for member in self.members.iter() {
+            let name = member.source_files.name();
+
+            if self.quiet {
+                eprintln!("   \x1B[32m\x1b[1mMember\x1b[0m: {}", name);
+            }
+
+            let options = self.package_options(&member.source_files, options.clone())?;
+
+            let member_build_directory =
+                BuildDirectory::new(Some(build_directory.path.join(&name)))
+                    .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?;
+
+            let member_output = output_directory.base().join(&name);
+            std::fs::create_dir_all(&member_output).map_err(io_error)?;
+            let member_output_directory = OutputDirectory::new(Some(member_output))
+                .map_err(|err| Box::new(IOError::Anyhow(err)) as Box<dyn Exception>)?;
+
+            if let Err(err) = step(
+                member,
+                &member_build_directory,
+                &member_output_directory,
+                &options,
+            ) {
+                errors.push(format!("{}: {}", name, err));
+
+                if !options.keep_going {
+                    break;
+                }
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Box::new(IOError::Other(format!(
+                "{} of {} members failed\n\n{}",
+                errors.len(),
+                self.members.len(),
+                errors.join("\n\n")
+            ))));
+        }
+
+        Ok(())
+    }
+
+    fn keep_build_directory(&mut self, keep: bool) -> Result<()> {
+        let build_directory = self.build_directory.as_mut().ok_or_else(|| {
+            Box::new(IOError::Other("Build file is not initialised".to_string()))
+                as Box<dyn Exception>
+        })?;
+
+        if keep {
+            build_directory.keep();
+            eprintln!(
+                "   \x1B[33m\x1b[1mBuild Directory\x1b[0m: Generated packages kept at \x1B[34m\x1b[1m'{}'\x1b[0m",
+                build_directory.path.display()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Fills the options which default to files of the package: the vendored dependencies,
+    /// the lock file, and the dependency overrides and kind paths of the manifests of the
+    /// package and of its workspace
+    fn package_options(
+        &self,
+        source_files: &SourceFiles,
+        mut options: BuildOptions,
+    ) -> Result<BuildOptions> {
+        let base = source_files.base();
+
+        if options.vendor_dir.is_none() {
+            options.vendor_dir = match &self.settings.vendor_dir {
+                Some(vendor_dir) => Some(std::fs::canonicalize(vendor_dir).map_err(io_error)?),
+                None => [base, source_files.root()]
+                    .into_iter()
+                    .map(|dir| dir.join("vendor"))
+                    .find(|vendor_dir| vendor_dir.is_dir())
+                    .and_then(|vendor_dir| std::fs::canonicalize(vendor_dir).ok()),
+            };
+        }
+
+        if options.lock_file.is_none() {
+            options.lock_file = Some(base.join(LOCK_FILE));
+        }
+
+        let manifests = [Some(source_files), self.source_files.as_ref()]
+            .into_iter()
+            .flatten()
+            .filter_map(|source_files| Some((source_files.base(), source_files.manifest()?)));
+
+        // The overrides given on the command line take precedence, then those of the package
+        for (base, manifest) in manifests {
+            let mut patches = manifest.patch.clone();
+
+            for (kind, path) in manifest.kinds.iter() {
+                let name = get_kind_crate(kind).ok_or_else(|| {
+                    Box::new(IOError::Other(format!(
+                        "Unknown kind {} in the kinds of {}",
+                        kind, MANIFEST_FILE
+                    ))) as Box<dyn Exception>
+                })?;
+
+                patches.insert(name.to_string(), path.clone());
+            }
+
+            for (name, path) in patches {
+                if let Entry::Vacant(entry) = options.patches.entry(name) {
+                    entry.insert(std::fs::canonicalize(base.join(path)).map_err(io_error)?);
+                }
+            }
+        }
+
+        Ok(options)
     }
 }
@@ -2,20 +2,152 @@ use super::*;
 
 impl Parser for Composer {
     fn parse(&self, files: &SourceFiles) -> result::Result<()> {
-        match self.compile(&format!("{}.{}", ENTRY_FILE, FILE_EXTENSION), files) {
-            Ok(_) => Ok(()),
-            Err(err) => Err(Box::new(IOError::Anyhow(err))),
+        for entry in files.entries() {
+            if let Err(err) = self.compile(&entry.display().to_string(), files) {
+                return Err(Box::new(IOError::Anyhow(err)));
+            }
         }
+
+        Ok(())
     }
 
     fn build(
         &self,
         build_directory: &BuildDirectory,
         output_directory: &OutputDirectory,
-        quiet: bool,
+        options: &BuildOptions,
     ) -> result::Result<()> {
-        self.build_directory(&build_directory.path, output_directory.base(), quiet)
+        let reports = self
+            .build_directory(
+                &build_directory.path,
+                output_directory.base(),
+                options,
+                |workflow, stage, elapsed| {
+                    if options.verbose {
+                        print_progress(workflow, stage, elapsed);
+                    }
+                },
+            )
             .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)?;
+
+        let errors: Vec<String> = reports
+            .iter()
+            .filter_map(|report| report.error.as_ref().map(|error| error.to_string()))
+            .collect();
+
+        if options.verbose || !errors.is_empty() {
+            print_summary(&reports);
+        }
+
+        if let (true, Some(lock_file)) = (options.verbose, &options.lock_file) {
+            for report in reports.iter().filter(|report| report.lock_outdated) {
+                eprintln!(
+                    "   \x1B[33m\x1b[1mOutdated Lock\x1b[0m: {} uses dependencies which are not locked in \x1B[34m\x1b[1m'{}'\x1b[0m, run `composer update` to lock them",
+                    report.workflow,
+                    lock_file.display()
+                );
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Box::new(IOError::Other(format!(
+                "{} of {} workflows failed to build\n\n{}",
+                errors.len(),
+                reports.len(),
+                errors.join("\n\n")
+            ))));
+        }
+
         Ok(())
     }
+
+    fn update(
+        &self,
+        build_directory: &BuildDirectory,
+        options: &BuildOptions,
+    ) -> result::Result<()> {
+        let lock = self
+            .update_lock_file(&build_directory.path, options)
+            .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)?;
+
+        if let Some(lock_file) = &options.lock_file {
+            std::fs::write(lock_file, lock.to_string())
+                .map_err(|error| Box::new(IOError::Std(error)) as Box<dyn Exception>)?;
+
+            if options.verbose {
+                eprintln!(
+                    "   \x1B[32m\x1b[1mUpdated\x1b[0m: {} dependencies locked in \x1B[34m\x1b[1m'{}'\x1b[0m",
+                    lock.packages.len(),
+                    lock_file.display()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn lint(&self, severities: &BTreeMap<String, String>) -> result::Result<Vec<Lint>> {
+        self.lint(severities)
+            .map_err(|error| Box::new(IOError::Anyhow(error)) as Box<dyn Exception>)
+    }
+}
+
+fn print_progress(workflow: &str, stage: BuildStage, elapsed: Duration) {
+    let (color, label) = match stage {
+        BuildStage::Queued => ("34", "Queued"),
+        BuildStage::Generating => ("36", "Generating"),
+        BuildStage::Compiling => ("36", "Compiling"),
+        BuildStage::Cached => ("32", "Cached"),
+        BuildStage::Done => ("32", "Finished"),
+        BuildStage::Failed => ("31", "Failed"),
+        BuildStage::Skipped => ("33", "Skipped"),
+    };
+
+    match stage {
+        BuildStage::Queued | BuildStage::Skipped => {
+            eprintln!("\x1B[{color}m\x1b[1m{label:>12}\x1b[0m {workflow}")
+        }
+        _ => eprintln!("\x1B[{color}m\x1b[1m{label:>12}\x1b[0m {workflow} ({elapsed:.2?})"),
+    }
+}
+
+fn print_summary(reports: &[BuildReport]) {
+    let width = reports
+        .iter()
+        .map(|report| report.workflow.len())
+        .chain(["Workflow".len()])
+        .max()
+        .unwrap_or_default();
+
+    eprintln!(
+        "\n   \x1b[1m{:<width$}  {:<10}  {:>10}  {:>10}\x1b[0m",
+        "Workflow", "Status", "Time", "Size"
+    );
+
+    for report in reports {
+        let elapsed = match report.stage {
+            BuildStage::Skipped => "-".to_string(),
+            _ => format!("{:.2?}", report.elapsed),
+        };
+
+        let size = report.size.map_or("-".to_string(), format_size);
+
+        eprintln!(
+            "   {:<width$}  {:<10}  {:>10}  {:>10}",
+            report.workflow,
+            report.stage.to_string(),
+            elapsed,
+            size
+        );
+    }
+
+    eprintln!();
+}
+
+fn format_size(size: u64) -> String {
+    match size {
+        0..=1023 => format!("{size} B"),
+        1024..=1048575 => format!("{:.1} KiB", size as f64 / 1024.0),
+        _ => format!("{:.1} MiB", size as f64 / 1048576.0),
+    }
 }
@@ -8,11 +8,16 @@ pub use parser::*;
 mod echo;
 use composer_primitives::Result;
 
-use crate::errors::IOError;
+use crate::errors::{io_error, IOError};
 use crate::types::Parser;
 use composer_primitives::{
-    constant::{ENTRY_FILE, FILE_EXTENSION},
-    result, BuildDirectory, Exception, OutputDirectory, SourceFiles,
+    constant::MANIFEST_FILE, result, BuildDirectory, BuildSettings, Exception, OutputDirectory,
+    SourceFiles,
 };
-use echo_library::Composer;
+use echo_library::{
+    get_kind_crate, with_span, BuildOptions, BuildReport, BuildStage, Composer, Lint, Severity,
+    LOCK_FILE,
+};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
+use std::time::Duration;
@@ -6,6 +6,8 @@ pub trait Parser {
         &self,
         build_directory: &BuildDirectory,
         output_directory: &OutputDirectory,
-        quiet: bool,
+        options: &BuildOptions,
     ) -> Result<()>;
+    fn update(&self, build_directory: &BuildDirectory, options: &BuildOptions) -> Result<()>;
+    fn lint(&self, severities: &BTreeMap<String, String>) -> Result<Vec<Lint>>;
 }
@@ -1,2 +1,3 @@
 pub static FILE_EXTENSION: &str = "echo";
 pub static ENTRY_FILE: &str = "main";
+pub static MANIFEST_FILE: &str = "Composer.toml";
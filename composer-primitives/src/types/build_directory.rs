@@ -25,4 +25,12 @@ impl BuildDirectory {
             }
         })
     }
+
+    /// Keeps the directory on the disk once the build directory is dropped. A directory
+    /// given by the user is never removed, a temporary one is removed unless it is kept.
+    pub fn keep(&mut self) {
+        if let Some(temp_dir) = self.temp_dir.take() {
+            self.path = temp_dir.keep();
+        }
+    }
 }
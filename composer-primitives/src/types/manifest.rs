@@ -0,0 +1,180 @@
+use anyhow::Error;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::constant::{ENTRY_FILE, FILE_EXTENSION};
+
+/// Settings of a package, read from the optional `Composer.toml` at its root
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub package: Option<Package>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub workspace: Option<Workspace>,
+    #[serde(default)]
+    pub build: BuildSettings,
+    /// Local paths of the crates implementing the kinds, by kind name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub kinds: BTreeMap<String, PathBuf>,
+    /// Libraries of config files loaded with `@name//path`, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub dependencies: BTreeMap<String, Dependency>,
+    /// Severity of the lint rules, `allow`, `warn` or `deny`, by rule name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lints: BTreeMap<String, String>,
+    /// Local paths overriding dependencies of the generated packages, by crate name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub patch: BTreeMap<String, PathBuf>,
+    /// Build settings overriding the ones of `[build]`, by environment name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, BuildSettings>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Package {
+    pub name: String,
+    /// Config files evaluated to declare the workflows, relative to the package
+    #[serde(default = "default_entry")]
+    pub entry: Vec<PathBuf>,
+}
+
+/// Packages built together, whose config files can load each other with paths starting with
+/// `//`, relative to the root of the workspace
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Workspace {
+    /// Directories of the member packages, relative to the workspace
+    pub members: Vec<PathBuf>,
+}
+
+/// Library of config files shared by several packages
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    /// Directory of the library, relative to the package
+    pub path: PathBuf,
+}
+
+/// Build settings of a package, the command line flags take precedence over them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_dir: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub optimize_size: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub offline: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor_dir: Option<PathBuf>,
+}
+
+fn default_entry() -> Vec<PathBuf> {
+    vec![PathBuf::from(format!("{}.{}", ENTRY_FILE, FILE_EXTENSION))]
+}
+
+impl Manifest {
+    /// Creates the manifest of a new package with the default settings
+    pub fn new(name: &str) -> Self {
+        Manifest {
+            package: Some(Package {
+                name: name.to_string(),
+                entry: default_entry(),
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Reads the manifest at the given path, if there is one
+    pub fn read(path: &Path) -> Result<Option<Manifest>, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let manifest: Manifest = toml::from_str(&content)
+            .map_err(|err| Error::msg(format!("Invalid manifest {}: {}", path.display(), err)))?;
+
+        let error = match (&manifest.package, &manifest.workspace) {
+            (None, None) => Some("expected a [package] or a [workspace] section"),
+            (Some(package), _) if package.entry.is_empty() => Some("the package has no entry file"),
+            (_, Some(workspace)) if workspace.members.is_empty() => {
+                Some("the workspace has no members")
+            }
+            _ => manifest
+                .dependencies
+                .keys()
+                .find(|name| {
+                    name.is_empty()
+                        || !name
+                            .chars()
+                            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+                })
+                .map(|_| "dependency names may only contain letters, digits, `_` and `-`")
+                .or_else(|| {
+                    manifest
+                        .dependencies
+                        .contains_key("std")
+                        .then_some("the dependency name std is reserved for the standard library")
+                }),
+        };
+
+        if let Some(error) = error {
+            return Err(Error::msg(format!(
+                "Invalid manifest {}: {}",
+                path.display(),
+                error
+            )));
+        }
+
+        Ok(Some(manifest))
+    }
+
+    /// Returns the build settings of the given environment, those of `[build]` overridden by
+    /// those of `[env.<name>]`
+    pub fn settings(&self, env: Option<&str>) -> Result<BuildSettings, Error> {
+        let env = match env {
+            Some(name) => self.env.get(name).ok_or_else(|| {
+                Error::msg(format!(
+                    "Environment {} is not defined in the manifest, expected one of: {}",
+                    name,
+                    self.env.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))
+            })?,
+            None => return Ok(self.build.clone()),
+        };
+
+        let build = self.build.clone();
+
+        Ok(BuildSettings {
+            output: env.output.clone().or(build.output),
+            cache_dir: env.cache_dir.clone().or(build.cache_dir),
+            target: env.target.clone().or(build.target),
+            profile: env.profile.clone().or(build.profile),
+            optimize_size: env.optimize_size.or(build.optimize_size),
+            jobs: env.jobs.or(build.jobs),
+            offline: env.offline.or(build.offline),
+            vendor_dir: env.vendor_dir.clone().or(build.vendor_dir),
+        })
+    }
+}
+
+impl std::fmt::Display for Manifest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", toml::to_string(self).map_err(|_| std::fmt::Error)?)
+    }
+}
@@ -1,9 +1,11 @@
 pub mod build_directory;
+pub mod manifest;
 pub mod output_directory;
 pub mod result;
 pub mod source_files;
 
 pub use build_directory::*;
+pub use manifest::*;
 pub use output_directory::*;
 pub use result::*;
 pub use source_files::*;
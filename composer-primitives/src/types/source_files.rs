@@ -1,16 +1,23 @@
 use anyhow::Error;
-use std::{env::current_dir, ffi::OsStr, fs, path::PathBuf};
+use std::{
+    env::current_dir,
+    fs,
+    path::{Path, PathBuf},
+};
 
-use itertools::Either;
-use std::collections::HashSet;
-use walkdir::WalkDir;
+use std::collections::BTreeMap;
 
-use crate::constant::FILE_EXTENSION;
+use crate::constant::{ENTRY_FILE, FILE_EXTENSION, MANIFEST_FILE};
+use crate::types::Manifest;
 
 #[derive(Clone, Debug)]
 pub struct SourceFiles {
     base: PathBuf,
-    files: HashSet<PathBuf>,
+    /// Root of the workspace the package is a member of, or the base of the package
+    root: PathBuf,
+    manifest: Option<Manifest>,
+    /// Directories of the libraries the package depends on, by name
+    libraries: BTreeMap<String, PathBuf>,
 }
 
 impl SourceFiles {
@@ -28,43 +35,204 @@ impl SourceFiles {
             None => current_dir().unwrap(),
         };
 
-        let file_paths = fs::read_dir(&base)
-            .unwrap()
-            .into_iter()
-            .flat_map(|item| {
-                let item = item.unwrap();
-
-                if item.path().is_dir() {
-                    Either::Left(
-                        WalkDir::new(item.path())
-                            .into_iter()
-                            .filter_map(|e| e.ok())
-                            .filter(move |e| {
-                                e.path().extension() == Some(OsStr::new(FILE_EXTENSION))
-                            })
-                            .map(|e| e.into_path()),
-                    )
-                } else {
-                    Either::Right(Box::new(
-                        vec![item.path()]
-                            .into_iter()
-                            .filter(|e| e.extension() == Some(OsStr::new(FILE_EXTENSION))),
-                    ))
-                }
-            })
-            .collect::<HashSet<PathBuf>>();
+        let manifest = Manifest::read(&base.join(MANIFEST_FILE))?;
+        let root = find_workspace_root(&base)?;
+
+        let mut libraries = get_libraries(&base, manifest.as_ref());
+        if let Some(root) = &root {
+            let workspace = Manifest::read(&root.join(MANIFEST_FILE))?;
+
+            for (name, path) in get_libraries(root, workspace.as_ref()) {
+                libraries.entry(name).or_insert(path);
+            }
+        }
 
         Ok(SourceFiles {
+            root: root.unwrap_or_else(|| base.clone()),
             base,
-            files: file_paths,
+            manifest,
+            libraries,
         })
     }
 
-    pub fn files(&self) -> &HashSet<PathBuf> {
-        &self.files
+    /// Returns the member packages of the workspace declared by the manifest, or None if the
+    /// package is not a workspace
+    pub fn members(&self) -> Result<Option<Vec<SourceFiles>>, Error> {
+        let workspace = match self.manifest.as_ref().and_then(|m| m.workspace.as_ref()) {
+            Some(workspace) => workspace,
+            None => return Ok(None),
+        };
+
+        let mut members = Vec::new();
+
+        for member in workspace.members.iter() {
+            let path = self.base.join(member);
+
+            if !path.is_dir() {
+                return Err(Error::msg(format!(
+                    "Workspace member {} is not a directory",
+                    path.display()
+                )));
+            }
+
+            let mut member = SourceFiles::new(Some(path))?;
+            member.root = self.base.clone();
+
+            for (name, path) in self.libraries.iter() {
+                member
+                    .libraries
+                    .entry(name.clone())
+                    .or_insert_with(|| path.clone());
+            }
+
+            members.push(member);
+        }
+
+        Ok(Some(members))
+    }
+
+    /// Returns the path of a config file loaded by the package. A path starting with
+    /// `@name//` is relative to the library the package depends on under that name, one
+    /// starting with `//` to the root of the workspace, and any other path to the directory of
+    /// the file loading it, or to the base of the package for an entry file.
+    pub fn resolve(&self, module: &str, loaded_from: Option<&Path>) -> Result<PathBuf, Error> {
+        if let Some(module) = module.strip_prefix('@') {
+            let (name, path) = module.split_once("//").ok_or_else(|| {
+                Error::msg(format!("Invalid load @{}, expected @library//path", module))
+            })?;
+
+            let library = self.libraries.get(name).ok_or_else(|| {
+                Error::msg(format!(
+                    "Library {} is not a dependency of the package, it should be declared in the [dependencies] of {}",
+                    name, MANIFEST_FILE
+                ))
+            })?;
+
+            return Ok(library.join(path));
+        }
+
+        Ok(
+            match (
+                module.strip_prefix("//"),
+                loaded_from.and_then(Path::parent),
+            ) {
+                (Some(module), _) => self.root.join(module),
+                (None, Some(dir)) => dir.join(module),
+                (None, None) => self.base.join(module),
+            },
+        )
+    }
+
+    /// Returns the name of the package in its manifest, or the name of its directory
+    pub fn name(&self) -> String {
+        match self.manifest.as_ref().and_then(|m| m.package.as_ref()) {
+            Some(package) => package.name.clone(),
+            None => fs::canonicalize(&self.base)
+                .ok()
+                .and_then(|base| {
+                    base.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                })
+                .unwrap_or_default(),
+        }
     }
 
     pub fn base(&self) -> &PathBuf {
         &self.base
     }
+
+    /// Returns the manifest of the package, if it has a `Composer.toml`
+    pub fn manifest(&self) -> Option<&Manifest> {
+        self.manifest.as_ref()
+    }
+
+    /// Returns the config files evaluated to declare the workflows, relative to the base
+    /// directory: the entry files of the manifest, or `main.echo` without a manifest
+    pub fn entries(&self) -> Vec<PathBuf> {
+        match self.manifest.as_ref().and_then(|m| m.package.as_ref()) {
+            Some(package) => package.entry.clone(),
+            None => vec![PathBuf::from(format!("{}.{}", ENTRY_FILE, FILE_EXTENSION))],
+        }
+    }
+
+    pub fn root(&self) -> &PathBuf {
+        &self.root
+    }
+}
+
+/// Returns the directories of the libraries declared in the manifest, relative to the given
+/// base directory of the manifest
+fn get_libraries(base: &Path, manifest: Option<&Manifest>) -> BTreeMap<String, PathBuf> {
+    manifest
+        .map(|manifest| {
+            manifest
+                .dependencies
+                .iter()
+                .map(|(name, dependency)| (name.clone(), base.join(&dependency.path)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Finds the closest parent directory whose manifest lists the given package as a member of
+/// its workspace. An invalid manifest is only reported if it lists the package as a member,
+/// those of unrelated parent directories are skipped.
+fn find_workspace_root(base: &Path) -> Result<Option<PathBuf>, Error> {
+    let base = match fs::canonicalize(base) {
+        Ok(base) => base,
+        Err(_) => return Ok(None),
+    };
+
+    for root in base.ancestors().skip(1) {
+        let path = root.join(MANIFEST_FILE);
+
+        let members = match Manifest::read(&path) {
+            Ok(Some(Manifest {
+                workspace: Some(workspace),
+                ..
+            })) => workspace.members,
+            Ok(_) => continue,
+            Err(err) => {
+                if is_member(root, &read_members(&path), &base) {
+                    return Err(err);
+                }
+
+                continue;
+            }
+        };
+
+        if is_member(root, &members, &base) {
+            return Ok(Some(root.to_path_buf()));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Returns the members listed in the `[workspace]` of a manifest which could not be read as a
+/// [`Manifest`], or no members if it is not valid TOML
+fn read_members(path: &Path) -> Vec<PathBuf> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| content.parse::<toml::Table>().ok())
+        .and_then(|manifest| {
+            manifest
+                .get("workspace")?
+                .get("members")?
+                .as_array()
+                .cloned()
+        })
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|member| member.as_str().map(PathBuf::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn is_member(root: &Path, members: &[PathBuf], base: &Path) -> bool {
+    members
+        .iter()
+        .any(|member| fs::canonicalize(root.join(member)).is_ok_and(|member| member == base))
 }
@@ -1,10 +1,39 @@
 #![allow(unused_imports)]
 use paste::paste;
 use super::*;
-#[derive(Debug, Flow)]
+/// Graph of the tasks of a workflow. The timeouts of the workflow and of its tasks are
+/// checked once a task returns. OpenWhisk actions are also invoked with their timeout, so that
+/// OpenWhisk stops waiting for a hung action, the tasks of the other kinds cannot be
+/// interrupted inside the wasm and fail once they return.
+#[derive(Debug)]
 pub struct WorkflowGraph {
     edges: Vec<(usize, usize)>,
     nodes: Vec<Box<dyn Execute>>,
+    compensations: Vec<Compensation>,
+    completed: Vec<usize>,
+    timeouts: HashMap<usize, u64>,
+    timeout: Option<u64>,
+    retries: HashMap<usize, u32>,
+    started: Option<Instant>,
+}
+
+/// Returns the current time, or None on wasm32-unknown-unknown which provides no clock and
+/// panics on `Instant::now`. Workflows with timeouts are not built for that target.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now() -> Option<Instant> {
+    Some(Instant::now())
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now() -> Option<Instant> {
+    None
+}
+
+/// Task executed to undo the effects of a completed task when a later task fails
+#[derive(Debug, Clone)]
+pub struct Compensation {
+    task_index: usize,
+    task: Box<dyn Execute>,
 }
 
 impl WorkflowGraph {
@@ -12,8 +41,278 @@ impl WorkflowGraph {
         WorkflowGraph {
             nodes: Vec::with_capacity(size),
             edges: Vec::new(),
+            compensations: Vec::new(),
+            completed: Vec::new(),
+            timeouts: HashMap::new(),
+            timeout: None,
+            retries: HashMap::new(),
+            started: now(),
         }
     }
+
+    /// Adds a task to the graph and returns its index
+    pub fn add_node(&mut self, task: Box<dyn Execute>) -> usize {
+        self.nodes.push(task);
+        self.nodes.len() - 1
+    }
+
+    /// Adds edges from the tasks at the source indices to the tasks at the destination
+    /// indices, in the given order
+    pub fn add_edges(&mut self, edges: &[(usize, usize)]) {
+        self.edges.extend_from_slice(edges);
+    }
+
+    /// Registers the compensation task of the task at the given index
+    pub fn add_compensation(&mut self, task_index: usize, task: Box<dyn Execute>) {
+        self.compensations.push(Compensation { task_index, task });
+    }
+
+    /// Sets the maximum time in milliseconds the whole workflow may run, checked each time a
+    /// task returns
+    pub fn set_timeout(&mut self, timeout_ms: u64) {
+        self.timeout = Some(timeout_ms);
+    }
+
+    /// Sets the maximum time in milliseconds the task at the given index may run, checked
+    /// once the task returns
+    pub fn set_task_timeout(&mut self, task_index: usize, timeout_ms: u64) {
+        self.timeouts.insert(task_index, timeout_ms);
+    }
+
+    /// Sets the number of times the task at the given index is run again when it fails with
+    /// a retryable error
+    pub fn set_task_retries(&mut self, task_index: usize, retries: u32) {
+        self.retries.insert(task_index, retries);
+    }
+
+    /// Executes the task at the given index, running it again while it fails with a
+    /// retryable error and has retries left. The retries count against the timeouts, no
+    /// retry is made once the timeout of the task or of the workflow has passed.
+    fn execute_with_retries(
+        &mut self,
+        index: usize,
+        task_started: Option<Instant>,
+    ) -> Result<(), WorkflowError> {
+        let retries = self.retries.get(&index).copied().unwrap_or_default();
+        let mut attempt = 0;
+
+        loop {
+            match self.nodes[index].execute() {
+                Err(error)
+                    if error.retryable
+                        && attempt < retries
+                        && self.check_timeouts(index, task_started).is_ok() =>
+                {
+                    attempt += 1
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Checks the time taken by the task at the given index and by the workflow so far
+    /// against their timeouts, as soon as the task returns. This is the only bound of the
+    /// tasks which are not OpenWhisk actions. Timeout errors are not retryable, as running the
+    /// task again would only take longer.
+    fn check_timeouts(
+        &self,
+        index: usize,
+        task_started: Option<Instant>,
+    ) -> Result<(), WorkflowError> {
+        let action_name = self.nodes[index].get_action_name();
+
+        if let (Some(timeout_ms), Some(task_started)) = (self.timeouts.get(&index), task_started) {
+            let elapsed = task_started.elapsed().as_millis();
+
+            if elapsed > *timeout_ms as u128 {
+                return Err(WorkflowError::new(
+                    Phase::Run,
+                    format!(
+                        "Task {} timed out: ran for {} ms, limit is {} ms",
+                        action_name, elapsed, timeout_ms
+                    ),
+                )
+                .with_task(&action_name)
+                .with_retryable(false));
+            }
+        }
+
+        if let (Some(timeout_ms), Some(started)) = (self.timeout, self.started) {
+            let elapsed = started.elapsed().as_millis();
+
+            if elapsed > timeout_ms as u128 {
+                return Err(WorkflowError::new(
+                    Phase::Run,
+                    format!(
+                        "Workflow timed out in task {}: ran for {} ms, limit is {} ms",
+                        action_name, elapsed, timeout_ms
+                    ),
+                )
+                .with_task(&action_name)
+                .with_retryable(false));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the compensations of the completed tasks in the reverse order of their completion.
+    /// Each compensation task receives the output of the task it compensates. Returns the
+    /// original error along with the result of every compensation.
+    pub fn compensate(&mut self, mut error: WorkflowError) -> WorkflowError {
+        for index in self.completed.clone().iter().rev() {
+            let output = self.nodes[*index].get_task_output();
+
+            for compensation in self
+                .compensations
+                .iter_mut()
+                .filter(|compensation| compensation.task_index == *index)
+            {
+                let result = compensation
+                    .task
+                    .set_output_to_task(output.clone())
+                    .and_then(|_| compensation.task.execute());
+
+                let result = match result {
+                    Ok(()) => serde_json::json!({
+                        "task": compensation.task.get_action_name(),
+                        "output": compensation.task.get_task_output(),
+                    }),
+                    Err(error) => serde_json::json!({
+                        "task": compensation.task.get_action_name(),
+                        "error": error,
+                    }),
+                };
+
+                error.compensations.push(result);
+            }
+        }
+
+        error
+    }
+
+    /// Returns the indices of the tasks the given task depends on, in the order in which
+    /// the edges were added
+    pub fn parents(&self, index: usize) -> Vec<usize> {
+        self.edges
+            .iter()
+            .filter(|(_, destination)| *destination == index)
+            .map(|(source, _)| *source)
+            .collect()
+    }
+
+    /// Feeds the outputs of the parent tasks to the task at the given index and executes it.
+    /// The output of a single parent is passed as it is, outputs of multiple parents are
+    /// passed as a list in the order of the edges. When the task fails, the compensations of
+    /// the completed tasks are run before the error is returned.
+    pub fn dispatch(&mut self, index: usize) -> Result<&mut Self, WorkflowError> {
+        let task_started = now();
+        let parents = self.parents(index);
+
+        let result = match parents.len() {
+            0 => Ok(()),
+            1 => {
+                let output = self.nodes[parents[0]].get_task_output();
+                self.nodes[index].set_output_to_task(output)
+            }
+            _ => {
+                let outputs: Vec<Value> = parents
+                    .iter()
+                    .map(|parent| self.nodes[*parent].get_task_output())
+                    .collect();
+                self.nodes[index].set_output_to_task(outputs.into())
+            }
+        };
+
+        let result = result
+            .and_then(|_| self.execute_with_retries(index, task_started))
+            .and_then(|_| self.check_timeouts(index, task_started));
+
+        if let Err(error) = result {
+            return Err(self.compensate(error));
+        }
+
+        self.completed.push(index);
+        Ok(self)
+    }
+
+    /// Returns the result of the workflow. The output of a single sink task is returned as
+    /// it is, outputs of multiple sink tasks are returned keyed by the task name.
+    pub fn outputs(&self, sinks: &[(&str, usize)]) -> Value {
+        match sinks {
+            [(_, index)] => self.nodes[*index].get_task_output(),
+            _ => Value::Object(
+                sinks
+                    .iter()
+                    .map(|(name, index)| (name.to_string(), self.nodes[*index].get_task_output()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Implements `invoke`, running a task once with the `run` method of the derive macro of its
+/// kind. An OpenWhisk action is invoked with a timeout instead, OpenWhisk answering with the
+/// id of the activation rather than its result once the timeout expires.
+#[macro_export]
+macro_rules! impl_invoke {
+    ($name:ty) => {
+        impl $name {
+            pub fn invoke(&mut self) -> Result<(), WorkflowError> {
+                self.run().map_err(|error| {
+                    WorkflowError::new(Phase::Run, error).with_task(&self.action_name)
+                })
+            }
+        }
+    };
+    ($name:ty, $timeout_ms:expr) => {
+        impl $name {
+            pub fn invoke(&mut self) -> Result<(), WorkflowError> {
+                let client = self.openwhisk_client();
+                let payload = serde_json::to_value(self.input.clone()).map_err(|error| {
+                    WorkflowError::new(Phase::Run, error).with_task(&self.action_name)
+                })?;
+                let url = format!(
+                    "{}/api/v1/namespaces/{}/actions/{}?blocking=true&result=true&timeout={}",
+                    client.context.host(),
+                    client.context.namespace(),
+                    self.action_name,
+                    $timeout_ms
+                );
+
+                self.output = client
+                    .client
+                    .new_request(
+                        Some(HttpMethods::POST),
+                        &url,
+                        Some(client.context.auth()),
+                        Some(payload),
+                    )
+                    .and_then(|request| client.client.invoke_request(request))
+                    .map_err(|error| get_invoke_error(&self.action_name, error, $timeout_ms))?;
+
+                Ok(())
+            }
+        }
+    };
+}
+
+/// Returns the error of an OpenWhisk invocation with a timeout. OpenWhisk answers with the
+/// status 202 Accepted when the action did not complete in time, which is not retryable.
+pub fn get_invoke_error(action_name: &str, error: String, timeout_ms: u64) -> WorkflowError {
+    if error.contains("Status :202") {
+        return WorkflowError::new(
+            Phase::Run,
+            format!(
+                "Task {} timed out: OpenWhisk did not return its result within {} ms",
+                action_name, timeout_ms
+            ),
+        )
+        .with_task(action_name)
+        .with_retryable(false);
+    }
+
+    WorkflowError::new(Phase::Run, error).with_task(action_name)
 }
 
 #[macro_export]
@@ -22,23 +321,166 @@ macro_rules! impl_execute_trait {
 
             paste!{
                 $( impl Execute for $struct {
-                    fn execute(&mut self) -> Result<(),String>{
-        self.run()
+                    fn execute(&mut self) -> Result<(), WorkflowError>{
+        self.invoke()
     }
 
     fn get_task_output(&self) -> Value {
         self.output().clone().into()
     }
 
-    fn set_output_to_task(&mut self, input: Value) {
+    fn set_output_to_task(&mut self, input: Value) -> Result<(), WorkflowError> {
         self.setter(input)
     }
+
+    fn get_action_name(&self) -> String {
+        self.action_name.clone()
+    }
                 }
             )*
         }
     };
 }
 
+#[macro_export]
+macro_rules! impl_repeat_until {
+    (
+        $name:ty,
+        $field:expr,
+        $value:expr,
+        $max_iterations:expr,
+        [$($element:ident : $key:expr),*]
+    ) => {
+        impl Execute for $name {
+            fn execute(&mut self) -> Result<(), WorkflowError> {
+                let until: Value = serde_json::from_str($value).map_err(|error| {
+                    WorkflowError::new(Phase::Output, error)
+                        .with_task(&self.action_name)
+                        .with_field($field)
+                })?;
+
+                for _ in 0..$max_iterations {
+                    self.invoke()?;
+
+                    if self.output().get($field) == Some(&until) {
+                        return Ok(());
+                    }
+
+                    $(
+                        let val = self.output().get($key).cloned().ok_or_else(|| {
+                            WorkflowError::new(Phase::Output, "missing field in the output of the task")
+                                .with_task(&self.action_name)
+                                .with_field($key)
+                        })?;
+                        self.input.$element = serde_json::from_value(val).map_err(|error| {
+                            WorkflowError::new(Phase::Setter, error)
+                                .with_task(&self.action_name)
+                                .with_field(stringify!($element))
+                        })?;
+                    )*
+                }
+
+                Err(WorkflowError::new(
+                    Phase::Run,
+                    format!("loop condition not satisfied after {} iterations", $max_iterations),
+                )
+                .with_task(&self.action_name)
+                .with_field($field))
+            }
+
+            fn get_task_output(&self) -> Value {
+                self.output().clone().into()
+            }
+
+            fn set_output_to_task(&mut self, input: Value) -> Result<(), WorkflowError> {
+                self.setter(input)
+            }
+
+            fn get_action_name(&self) -> String {
+                self.action_name.clone()
+            }
+        }
+    };
+}
+
+/// Rule a field of the workflow input has to satisfy, declared on the argument in the
+/// config file
+#[derive(Debug, Clone, Copy)]
+pub enum Constraint {
+    Min(f64),
+    Max(f64),
+    /// The pattern along with the function matching a value against it, generated only for
+    /// the workflows using patterns so that the others do not depend on regex
+    Pattern(&'static str, fn(&str) -> bool),
+    /// JSON array of the allowed values
+    OneOf(&'static str),
+    MinLen(usize),
+    NonEmpty,
+}
+
+impl Constraint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constraint::Min(_) => "min",
+            Constraint::Max(_) => "max",
+            Constraint::Pattern(..) => "pattern",
+            Constraint::OneOf(_) => "one_of",
+            Constraint::MinLen(_) => "min_len",
+            Constraint::NonEmpty => "non_empty",
+        }
+    }
+
+    /// Checks the value of the given input field against the rule
+    pub fn check<T: Serialize>(&self, field: &str, value: &T) -> Result<(), WorkflowError> {
+        let value = serde_json::to_value(value).map_err(|error| {
+            WorkflowError::new(Phase::Input, error).with_field(field)
+        })?;
+
+        let length = match &value {
+            Value::String(value) => value.chars().count(),
+            Value::Array(value) => value.len(),
+            Value::Object(value) => value.len(),
+            _ => 0,
+        };
+
+        let satisfied = match self {
+            Constraint::Min(min) => value.as_f64().map_or(false, |value| value >= *min),
+            Constraint::Max(max) => value.as_f64().map_or(false, |value| value <= *max),
+            Constraint::Pattern(_, is_match) => value.as_str().map_or(false, is_match),
+            // Numbers are compared by value, an integer allowed for a float field matches it
+            Constraint::OneOf(values) => serde_json::from_str::<Vec<Value>>(values)
+                .map_or(false, |values| {
+                    values.iter().any(|allowed| match (allowed.as_f64(), value.as_f64()) {
+                        (Some(allowed), Some(value)) => allowed == value,
+                        _ => allowed == &value,
+                    })
+                }),
+            Constraint::MinLen(min_len) => length >= *min_len,
+            Constraint::NonEmpty => length > 0,
+        };
+
+        if satisfied {
+            return Ok(());
+        }
+
+        let expected = match self {
+            Constraint::Min(min) => format!("be at least {}", min),
+            Constraint::Max(max) => format!("be at most {}", max),
+            Constraint::Pattern(pattern, _) => format!("match {:?}", pattern),
+            Constraint::OneOf(values) => format!("be one of {}", values),
+            Constraint::MinLen(min_len) => format!("have a length of at least {}", min_len),
+            Constraint::NonEmpty => "not be empty".to_string(),
+        };
+
+        Err(WorkflowError::new(
+            Phase::Input,
+            format!("value {} must {}", value, expected),
+        )
+        .with_field(field)
+        .with_rule(self.name()))
+    }
+}
+
 #[allow(dead_code, unused)]
 pub fn join_hashmap<T: PartialEq + std::hash::Hash + Eq + Clone, U: Clone, V: Clone>(
     first: HashMap<T, U>,
@@ -61,10 +503,13 @@ pub unsafe extern "C" fn free_memory(ptr: *mut u8, size: u32, alignment: u32) {
     alloc::alloc::dealloc(ptr, layout);
 }
 
+#[cfg(not(test))]
 #[link(wasm_import_module = "host")]
 extern "C" {
     pub fn set_output(ptr: i32, size: i32);
 }
+/// Output of the wasm, where the result holds either the workflow result under `Ok` or the
+/// `WorkflowError` under `Err`
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Output {
     pub result: Value,
@@ -0,0 +1,90 @@
+use super::*;
+
+/// Stage of the workflow execution in which an error occurred
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Deserialization of the workflow input
+    Input,
+    /// Passing the output of the previous tasks to the input of a task
+    Setter,
+    /// Execution of a task
+    Run,
+    /// Processing the output of a task
+    Output,
+}
+
+/// Error returned by the workflow, serialized into the `Output` of the wasm
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WorkflowError {
+    pub task: Option<String>,
+    pub phase: Phase,
+    pub field: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rule: Option<String>,
+    pub message: String,
+    pub retryable: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compensations: Vec<Value>,
+}
+
+impl WorkflowError {
+    /// Creates an error of the given phase. Only errors raised while running a task are
+    /// considered retryable, the others fail again for the same payload.
+    pub fn new(phase: Phase, message: impl ToString) -> Self {
+        WorkflowError {
+            task: None,
+            phase,
+            field: None,
+            rule: None,
+            message: message.to_string(),
+            retryable: phase == Phase::Run,
+            compensations: Vec::new(),
+        }
+    }
+
+    pub fn with_task(mut self, task: &str) -> Self {
+        self.task = Some(task.to_string());
+        self
+    }
+
+    pub fn with_field(mut self, field: &str) -> Self {
+        self.field = Some(field.to_string());
+        self
+    }
+
+    pub fn with_rule(mut self, rule: &str) -> Self {
+        self.rule = Some(rule.to_string());
+        self
+    }
+
+    pub fn with_retryable(mut self, retryable: bool) -> Self {
+        self.retryable = retryable;
+        self
+    }
+}
+
+impl std::fmt::Display for WorkflowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(task) = &self.task {
+            write!(f, "{}: ", task)?;
+        }
+
+        if let Some(field) = &self.field {
+            write!(f, "{}: ", field)?;
+        }
+
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Deserializes a value, reporting the path of the offending field on failure
+pub fn from_value_at<T: serde::de::DeserializeOwned>(
+    value: Value,
+    phase: Phase,
+) -> Result<T, WorkflowError> {
+    serde_path_to_error::deserialize(value).map_err(|error| {
+        let field = error.path().to_string();
+        WorkflowError::new(phase, error.into_inner()).with_field(&field)
+    })
+}
@@ -7,11 +7,13 @@
 #![allow(unused_must_use)]
 
 mod common;
+mod errors;
 mod macros;
 mod traits;
 mod types;
 
 use common::*;
+use errors::*;
 use derive_enum_from_into::{EnumFrom, EnumTryInto};
 use dyn_clone::{clone_trait_object, DynClone};
 use macros::*;
@@ -23,16 +25,18 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::convert::TryInto;
 use std::fmt::Debug;
+use std::time::Instant;
 use traits::*;
 use types::*;
-use workflow_macro::Flow;
 extern crate alloc;
 use codec::{Decode, Encode};
 use core::alloc::Layout;
 
+// The test harness provides its own entry point and has no host to import from
+#[cfg(not(test))]
 #[no_mangle]
 pub fn _start(ptr: *mut u8, length: i32) {
-    let result: Value;
+    let result: Result<Value, WorkflowError>;
     unsafe {
         let mut vect = Vec::new();
         for i in 1..=length {
@@ -41,10 +45,11 @@ pub fn _start(ptr: *mut u8, length: i32) {
             }
             *ptr = *ptr.add(i as usize);
         }
-        result = serde_json::from_slice(&vect).unwrap();
+        result = serde_json::from_slice(&vect)
+            .map_err(|error| WorkflowError::new(Phase::Input, error));
     }
 
-    let res = main(result);
+    let res = result.and_then(main);
     let output = Output {
         result: serde_json::to_value(res).unwrap(),
     };
@@ -92,11 +92,23 @@ macro_rules! impl_setter {
         [$($element:ident : $key:expr),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
+            pub fn setter(&mut self, value: Value) -> Result<(), WorkflowError> {
                 $(
-                    let val = value.get($key).unwrap();
-                    self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                    let val = value.get($key).ok_or_else(|| {
+                        WorkflowError::new(
+                            Phase::Setter,
+                            format!("missing field `{}` in the output of the previous task", $key),
+                        )
+                        .with_task(&self.action_name)
+                        .with_field(stringify!($element))
+                    })?;
+                    self.input.$element = serde_json::from_value(val.clone()).map_err(|error| {
+                        WorkflowError::new(Phase::Setter, error)
+                            .with_task(&self.action_name)
+                            .with_field(stringify!($element))
+                    })?;
                 )*
+                Ok(())
             }
         }
     }
@@ -111,20 +123,40 @@ macro_rules! impl_map_setter {
         $out:expr
     ) => {
         impl $name {
-            pub fn setter(&mut self, val: Value) {
-                
-                    let value = val.get($key).unwrap();
-                    let value = serde_json::from_value::<Vec<$typ_name>>(value.clone()).unwrap();
-                    let mut map: HashMap<_, _> = value
-                        .iter()
-                        .map(|x| {
-                            self.input.$element = x.to_owned() as $typ_name;
-                            self.run();
-                            (x.to_owned(), self.output.get($out).unwrap().to_owned())
-                        })
-                        .collect();
-                    self.mapout = to_value(map).unwrap();
-                
+            pub fn setter(&mut self, val: Value) -> Result<(), WorkflowError> {
+                let value = val.get($key).ok_or_else(|| {
+                    WorkflowError::new(
+                        Phase::Setter,
+                        format!("missing field `{}` in the output of the previous task", $key),
+                    )
+                    .with_task(&self.action_name)
+                    .with_field(stringify!($element))
+                })?;
+                let value = serde_json::from_value::<Vec<$typ_name>>(value.clone()).map_err(|error| {
+                    WorkflowError::new(Phase::Setter, error)
+                        .with_task(&self.action_name)
+                        .with_field(stringify!($element))
+                })?;
+
+                let mut map = HashMap::new();
+
+                for x in value.iter() {
+                    self.input.$element = x.to_owned() as $typ_name;
+                    self.invoke()?;
+
+                    let output = self.output.get($out).ok_or_else(|| {
+                        WorkflowError::new(Phase::Output, "missing field in the output of the task")
+                            .with_task(&self.action_name)
+                            .with_field($out)
+                    })?;
+
+                    map.insert(x.to_owned(), output.to_owned());
+                }
+
+                self.mapout = to_value(map).map_err(|error| {
+                    WorkflowError::new(Phase::Output, error).with_task(&self.action_name)
+                })?;
+                Ok(())
             }
         }
     }
@@ -137,14 +169,29 @@ macro_rules! impl_concat_setter {
         $input:ident
     ) => {
         impl $name{
-            pub fn setter(&mut self, val: Value) {
-                
-                    let val: Vec<Value> = serde_json::from_value(val).unwrap();
-                    let res = join_hashmap(
-                        serde_json::from_value(val[0].to_owned()).unwrap(),
-                        serde_json::from_value(val[1].to_owned()).unwrap(),
-                    );
-                    self.input.$input = res;
+            pub fn setter(&mut self, val: Value) -> Result<(), WorkflowError> {
+                let action_name = self.action_name.clone();
+                let error = |error: String| {
+                    WorkflowError::new(Phase::Setter, error)
+                        .with_task(&action_name)
+                        .with_field(stringify!($input))
+                };
+
+                let val: Vec<Value> = serde_json::from_value(val).map_err(|e| error(e.to_string()))?;
+
+                if val.len() < 2 {
+                    return Err(error(format!(
+                        "expected the outputs of two tasks, found {}",
+                        val.len()
+                    )));
+                }
+
+                let res = join_hashmap(
+                    serde_json::from_value(val[0].to_owned()).map_err(|e| error(e.to_string()))?,
+                    serde_json::from_value(val[1].to_owned()).map_err(|e| error(e.to_string()))?,
+                );
+                self.input.$input = res;
+                Ok(())
             }
         }
     }
@@ -161,19 +208,52 @@ macro_rules! impl_combine_setter {
             $element:ident : $key:expr),*]
     ) => {
         impl $name{
-            pub fn setter(&mut self, value: Value) {
-
-                let value: Vec<Value> = serde_json::from_value(value).unwrap();
+            pub fn setter(&mut self, value: Value) -> Result<(), WorkflowError> {
+                let action_name = self.action_name.clone();
+                let value: Vec<Value> = serde_json::from_value(value).map_err(|error| {
+                    WorkflowError::new(Phase::Setter, error).with_task(&action_name)
+                })?;
                 $(
+                    let error = |error: String| {
+                        WorkflowError::new(Phase::Setter, error)
+                            .with_task(&action_name)
+                            .with_field(stringify!($element))
+                    };
+
+                    let output = value.get($($index)*).ok_or_else(|| {
+                        error(format!("missing the output of the previous task {}", $($index)*))
+                    })?;
+
                     if stringify!($($value_input)*).is_empty(){
-                        let val = value[$($index)*].get($key).unwrap();
-                        self.input.$element = serde_json::from_value(val.clone()).unwrap();
+                        let val = output.get($key).ok_or_else(|| {
+                            error(format!("missing field `{}` in the output of the previous task", $key))
+                        })?;
+                        self.input.$element = serde_json::from_value(val.clone()).map_err(|e| error(e.to_string()))?;
                     }else{
-                        self.input.$element = serde_json::from_value(value[$($index)*].to_owned()).unwrap();
+                        self.input.$element = serde_json::from_value(output.to_owned()).map_err(|e| error(e.to_string()))?;
                     }
                 )*
+                Ok(())
             }
         }
     }
 }
 
+#[macro_export]
+macro_rules! impl_validate {
+    (
+        $name:ty,
+        [$($field:ident : [$($constraint:expr),*]),*]
+    ) => {
+        impl $name {
+            pub fn validate(&self) -> Result<(), WorkflowError> {
+                $(
+                    $(
+                        $constraint.check(stringify!($field), &self.$field)?;
+                    )*
+                )*
+                Ok(())
+            }
+        }
+    };
+}
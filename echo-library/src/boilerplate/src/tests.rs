@@ -0,0 +1,252 @@
+//! Tests of the runtime of the generated packages, added to a generated package and run with
+//! `cargo test` by the tests of echo-library. They are not part of the packages generated by
+//! `composer build`.
+use super::*;
+use serde_json::json;
+
+/// Task failing with an error of the given phase for its first `failures` executions, then
+/// returning its input along with the number of executions
+#[derive(Debug, Clone)]
+struct MockTask {
+    action_name: String,
+    failures: u32,
+    phase: Phase,
+    sleep_ms: u64,
+    attempts: u32,
+    input: Value,
+    output: Value,
+}
+
+impl MockTask {
+    fn new(action_name: &str) -> Self {
+        MockTask {
+            action_name: action_name.to_string(),
+            failures: 0,
+            phase: Phase::Run,
+            sleep_ms: 0,
+            attempts: 0,
+            input: Value::Null,
+            output: Value::Null,
+        }
+    }
+
+    fn failing(action_name: &str, failures: u32, phase: Phase) -> Self {
+        MockTask {
+            failures,
+            phase,
+            ..MockTask::new(action_name)
+        }
+    }
+}
+
+impl Execute for MockTask {
+    fn execute(&mut self) -> Result<(), WorkflowError> {
+        self.attempts += 1;
+        std::thread::sleep(std::time::Duration::from_millis(self.sleep_ms));
+
+        if self.attempts <= self.failures {
+            return Err(WorkflowError::new(
+                self.phase,
+                format!("attempt {} failed", self.attempts),
+            )
+            .with_task(&self.action_name));
+        }
+
+        self.output = json!({ "input": self.input, "attempts": self.attempts });
+        Ok(())
+    }
+
+    fn get_task_output(&self) -> Value {
+        self.output.clone()
+    }
+
+    fn set_output_to_task(&mut self, input: Value) -> Result<(), WorkflowError> {
+        self.input = input;
+        Ok(())
+    }
+
+    fn get_action_name(&self) -> String {
+        self.action_name.clone()
+    }
+}
+
+#[test]
+fn dispatch_passes_the_outputs_of_the_parents() {
+    let mut workflow = WorkflowGraph::new(3);
+    let first = workflow.add_node(Box::new(MockTask::new("first")));
+    let second = workflow.add_node(Box::new(MockTask::new("second")));
+    let last = workflow.add_node(Box::new(MockTask::new("last")));
+    workflow.add_edges(&[(first, last), (second, last)]);
+
+    let result = workflow
+        .dispatch(first)
+        .and_then(|workflow| workflow.dispatch(second))
+        .and_then(|workflow| workflow.dispatch(last))
+        .unwrap()
+        .outputs(&[("last", last), ("second", second)]);
+
+    assert_eq!(
+        result["last"]["input"],
+        json!([
+            { "input": null, "attempts": 1 },
+            { "input": null, "attempts": 1 }
+        ])
+    );
+    assert_eq!(result["second"]["attempts"], 1);
+}
+
+#[test]
+fn retryable_errors_are_retried() {
+    let mut workflow = WorkflowGraph::new(1);
+    let index = workflow.add_node(Box::new(MockTask::failing("flaky", 2, Phase::Run)));
+    workflow.set_task_retries(index, 2);
+
+    let result = workflow.dispatch(index).unwrap().outputs(&[("flaky", index)]);
+    assert_eq!(result["attempts"], 3);
+
+    let mut workflow = WorkflowGraph::new(1);
+    let index = workflow.add_node(Box::new(MockTask::failing("flaky", 2, Phase::Run)));
+    workflow.set_task_retries(index, 1);
+
+    let error = workflow.dispatch(index).unwrap_err();
+    assert_eq!(error.message, "attempt 2 failed");
+    assert!(error.retryable);
+}
+
+#[test]
+fn errors_which_are_not_retryable_are_not_retried() {
+    let mut workflow = WorkflowGraph::new(1);
+    let index = workflow.add_node(Box::new(MockTask::failing("setter", 1, Phase::Setter)));
+    workflow.set_task_retries(index, 3);
+
+    let error = workflow.dispatch(index).unwrap_err();
+    assert_eq!(error.message, "attempt 1 failed");
+    assert_eq!(error.phase, Phase::Setter);
+    assert!(!error.retryable);
+}
+
+#[test]
+fn timeouts_are_checked_once_the_task_returns() {
+    let mut workflow = WorkflowGraph::new(1);
+    let index = workflow.add_node(Box::new(MockTask {
+        sleep_ms: 20,
+        ..MockTask::new("slow")
+    }));
+    workflow.set_task_timeout(index, 5);
+    workflow.set_task_retries(index, 3);
+
+    let error = workflow.dispatch(index).unwrap_err();
+    assert!(error.message.starts_with("Task slow timed out"));
+    assert!(!error.retryable);
+}
+
+#[test]
+fn retries_stop_once_the_timeout_has_passed() {
+    let mut workflow = WorkflowGraph::new(1);
+    let index = workflow.add_node(Box::new(MockTask {
+        sleep_ms: 20,
+        ..MockTask::failing("flaky", 5, Phase::Run)
+    }));
+    workflow.set_timeout(30);
+    workflow.set_task_retries(index, 5);
+
+    let error = workflow.dispatch(index).unwrap_err();
+    assert_eq!(error.message, "attempt 2 failed");
+}
+
+#[test]
+fn completed_tasks_are_compensated_in_reverse_order() {
+    let mut workflow = WorkflowGraph::new(3);
+    let first = workflow.add_node(Box::new(MockTask::new("first")));
+    let second = workflow.add_node(Box::new(MockTask::new("second")));
+    let last = workflow.add_node(Box::new(MockTask::failing("last", 1, Phase::Run)));
+    workflow.add_edges(&[(first, second), (second, last)]);
+    workflow.add_compensation(first, Box::new(MockTask::new("undo_first")));
+    workflow.add_compensation(second, Box::new(MockTask::new("undo_second")));
+
+    let error = workflow
+        .dispatch(first)
+        .and_then(|workflow| workflow.dispatch(second))
+        .and_then(|workflow| workflow.dispatch(last))
+        .unwrap_err();
+
+    assert_eq!(error.task.as_deref(), Some("last"));
+    assert_eq!(error.compensations.len(), 2);
+    assert_eq!(error.compensations[0]["task"], "undo_second");
+    assert_eq!(error.compensations[1]["task"], "undo_first");
+    assert_eq!(
+        error.compensations[1]["output"]["input"],
+        json!({ "input": null, "attempts": 1 })
+    );
+}
+
+#[test]
+fn workflow_errors() {
+    let error = WorkflowError::new(Phase::Run, "failed").with_task("salary");
+    assert!(error.retryable);
+    assert_eq!(error.to_string(), "salary: failed");
+
+    let error = WorkflowError::new(Phase::Input, "invalid").with_field("role");
+    assert!(!error.retryable);
+    assert_eq!(
+        serde_json::to_value(&error).unwrap(),
+        json!({
+            "task": null,
+            "phase": "input",
+            "field": "role",
+            "message": "invalid",
+            "retryable": false
+        })
+    );
+
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        #[allow(dead_code)]
+        price: u32,
+    }
+
+    let error = from_value_at::<Vec<Item>>(json!([{ "price": 1 }, { "price": "two" }]), Phase::Setter)
+        .unwrap_err();
+    assert_eq!(error.phase, Phase::Setter);
+    assert_eq!(error.field.as_deref(), Some("[1].price"));
+    assert!(!error.retryable);
+}
+
+#[test]
+fn constraints() {
+    assert!(Constraint::Min(1.0).check("amount", &1).is_ok());
+
+    let error = Constraint::Max(10.0).check("amount", &11).unwrap_err();
+    assert_eq!(error.field.as_deref(), Some("amount"));
+    assert_eq!(error.rule.as_deref(), Some("max"));
+    assert_eq!(error.message, "value 11 must be at most 10");
+
+    assert!(Constraint::OneOf("[\"admin\", \"user\"]")
+        .check("role", &"user")
+        .is_ok());
+    // The values allowed for a float field are rounded to f32 by the generated code
+    let one_of = Constraint::OneOf("[1, 0.10000000149011612]");
+    assert!(one_of.check("rate", &1.0f32).is_ok());
+    assert!(one_of.check("rate", &0.1f32).is_ok());
+    assert!(one_of.check("rate", &0.2f32).is_err());
+    assert!(Constraint::NonEmpty.check("ids", &Vec::<u32>::new()).is_err());
+}
+
+#[test]
+fn openwhisk_timeouts_are_not_retried() {
+    let error = get_invoke_error(
+        "salary",
+        ": Error -> [ Status :202 Accepted, Message : {\"activationId\":\"1\"} ]".to_string(),
+        500,
+    );
+    assert_eq!(error.task.as_deref(), Some("salary"));
+    assert_eq!(
+        error.message,
+        "Task salary timed out: OpenWhisk did not return its result within 500 ms"
+    );
+    assert!(!error.retryable);
+
+    let error = get_invoke_error("salary", "connection refused".to_string(), 500);
+    assert_eq!(error.message, "connection refused");
+    assert!(error.retryable);
+}
@@ -2,9 +2,10 @@
 use super::*;
 
 pub trait Execute : Debug + DynClone  {
-    fn execute(&mut self)-> Result<(),String>;
+    fn execute(&mut self)-> Result<(), WorkflowError>;
     fn get_task_output(&self)->Value;
-    fn set_output_to_task(&mut self, inp: Value);
+    fn set_output_to_task(&mut self, inp: Value) -> Result<(), WorkflowError>;
+    fn get_action_name(&self) -> String;
 }
 
 clone_trait_object!(Execute);
@@ -1,6 +1,6 @@
 use super::*;
 
 #[allow(dead_code, unused)]
-pub fn main(args: Value) -> Result<Value, String> {
+pub fn main(args: Value) -> Result<Value, WorkflowError> {
     Ok(serde_json::json!({}))
 }
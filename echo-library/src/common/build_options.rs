@@ -0,0 +1,336 @@
+use super::*;
+use fnv::FnvHasher;
+use std::hash::Hasher;
+use std::time::Duration;
+
+/// Options controlling how the generated packages of the workflows are built
+#[derive(Debug, Clone, Default)]
+pub struct BuildOptions {
+    /// Whether the output of cargo should be shown
+    pub verbose: bool,
+    /// Whether the generated packages are kept after a successful build
+    pub keep_build: bool,
+    /// Directory holding the cargo target directory shared by all the workflows and the
+    /// wasm of the previous successful builds
+    pub cache_dir: PathBuf,
+    /// Maximum number of workflows built at the same time, the number of CPUs when zero
+    pub jobs: usize,
+    /// Whether the remaining workflows are still built after a workflow fails to build
+    pub keep_going: bool,
+    /// Whether cargo is run without accessing the network
+    pub offline: bool,
+    /// Directory holding local copies of the dependencies, replacing crates.io and the git
+    /// repositories of the kinds
+    pub vendor_dir: Option<PathBuf>,
+    /// Local paths overriding dependencies of the generated packages, by crate name
+    pub patches: BTreeMap<String, PathBuf>,
+    pub target: Target,
+    pub profile: Profile,
+    /// Whether the wasm is compiled for small code size and shrunk with `wasm-opt` once built
+    pub optimize_size: bool,
+    /// Path of the `composer.lock` pinning the dependencies of the generated packages
+    pub lock_file: Option<PathBuf>,
+}
+
+/// Platform the generated packages are compiled for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Target {
+    #[default]
+    Wasip1,
+    WasmUnknown,
+    /// The platform composer runs on, used to test the generated packages
+    Native,
+}
+
+impl Target {
+    /// Returns the target triple passed to cargo, or None for the native platform
+    pub fn triple(&self) -> Option<&'static str> {
+        match self {
+            Target::Wasip1 => Some("wasm32-wasip1"),
+            Target::WasmUnknown => Some("wasm32-unknown-unknown"),
+            Target::Native => None,
+        }
+    }
+
+    /// Returns the file name of the library built by cargo for the given package
+    pub fn artifact_name(&self, package_name: &str) -> String {
+        match self {
+            Target::Native => format!(
+                "{}{}{}",
+                std::env::consts::DLL_PREFIX,
+                package_name,
+                std::env::consts::DLL_SUFFIX
+            ),
+            _ => format!("{package_name}.wasm"),
+        }
+    }
+
+    /// Returns the extension of the file written to the output directory
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Target::Native => std::env::consts::DLL_EXTENSION,
+            _ => "wasm",
+        }
+    }
+}
+
+impl std::str::FromStr for Target {
+    type Err = String;
+
+    fn from_str(target: &str) -> Result<Self, Self::Err> {
+        match target {
+            "wasm32-wasip1" => Ok(Target::Wasip1),
+            "wasm32-unknown-unknown" => Ok(Target::WasmUnknown),
+            "native" => Ok(Target::Native),
+            _ => Err(format!(
+                "unsupported target {target}, expected one of wasm32-wasip1, wasm32-unknown-unknown, native"
+            )),
+        }
+    }
+}
+
+impl Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.triple().unwrap_or("native"))
+    }
+}
+
+/// Cargo profile the generated packages are compiled with
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Profile {
+    Debug,
+    #[default]
+    Release,
+}
+
+impl Profile {
+    /// Returns the directory of the profile in the cargo target directory
+    pub fn dir(&self) -> &'static str {
+        match self {
+            Profile::Debug => "debug",
+            Profile::Release => "release",
+        }
+    }
+}
+
+impl std::str::FromStr for Profile {
+    type Err = String;
+
+    fn from_str(profile: &str) -> Result<Self, Self::Err> {
+        match profile {
+            "debug" | "dev" => Ok(Profile::Debug),
+            "release" => Ok(Profile::Release),
+            _ => Err(format!(
+                "unsupported profile {profile}, expected one of debug, release"
+            )),
+        }
+    }
+}
+
+impl Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.dir())
+    }
+}
+
+/// Stage of the build of a workflow, reported as the build progresses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuildStage {
+    Queued,
+    Generating,
+    Compiling,
+    /// Built from the wasm of a previous build of the same package
+    Cached,
+    Done,
+    Failed,
+    /// Not built as another workflow failed to build
+    Skipped,
+}
+
+impl Display for BuildStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildStage::Queued => write!(f, "queued"),
+            BuildStage::Generating => write!(f, "generating"),
+            BuildStage::Compiling => write!(f, "compiling"),
+            BuildStage::Cached => write!(f, "cached"),
+            BuildStage::Done => write!(f, "done"),
+            BuildStage::Failed => write!(f, "failed"),
+            BuildStage::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// Result of the build of a workflow
+#[derive(Debug)]
+pub struct BuildReport {
+    /// Name of the workflow along with its version (i.e `name_version`)
+    pub workflow: String,
+    pub stage: BuildStage,
+    pub elapsed: Duration,
+    /// Size in bytes of the built wasm
+    pub size: Option<u64>,
+    pub error: Option<Error>,
+    /// Whether the lock file misses dependencies of the workflow, which were resolved freshly.
+    /// A build does not rewrite the lock file, `composer update` refreshes it.
+    pub lock_outdated: bool,
+}
+
+impl BuildOptions {
+    /// Returns the default cache directory, `composer` under the cache directory of the user
+    /// (i.e `~/.cache/composer`), or under the temporary directory if there is none
+    pub fn default_cache_dir() -> PathBuf {
+        dirs_next::cache_dir()
+            .unwrap_or_else(std::env::temp_dir)
+            .join("composer")
+    }
+
+    /// Returns the number of workflows built at the same time
+    pub fn jobs(&self) -> usize {
+        if self.jobs > 0 {
+            self.jobs
+        } else {
+            std::thread::available_parallelism().map_or(1, |jobs| jobs.get())
+        }
+    }
+
+    /// Returns the cargo target directory shared by the builds of all the workflows, so that
+    /// their common dependencies are compiled once and kept between builds. Cargo locks it
+    /// while compiling, the workflows are compiled one after the other while the other steps
+    /// of their builds run in parallel.
+    pub fn target_dir(&self) -> PathBuf {
+        self.cache_dir.join("target")
+    }
+
+    /// Returns the path of the wasm stored for the package with the given content hash
+    pub fn cached_wasm(&self, hash: &str) -> PathBuf {
+        self.cache_dir
+            .join("wasm")
+            .join(format!("{}.{}", hash, self.target.extension()))
+    }
+
+    /// Returns the path of the `Cargo.lock` the package with the given content hash was built
+    /// with, stored along with its wasm
+    pub fn cached_lock(&self, hash: &str) -> PathBuf {
+        self.cache_dir.join("wasm").join(format!("{}.lock", hash))
+    }
+
+    /// Returns the path of the library built by cargo for the given package
+    pub fn built_artifact(&self, package_name: &str) -> PathBuf {
+        let target_dir = match self.target.triple() {
+            Some(triple) => self.target_dir().join(triple),
+            None => self.target_dir(),
+        };
+
+        target_dir
+            .join(self.profile.dir())
+            .join(self.target.artifact_name(package_name))
+    }
+
+    /// Returns the section of the `Cargo.toml` of the generated packages configuring the
+    /// profile they are compiled with
+    pub fn cargo_profile(&self) -> String {
+        let opt_level = if self.optimize_size { "\"z\"" } else { "3" };
+
+        match self.profile {
+            Profile::Release => format!(
+                "[profile.release]
+lto = true
+codegen-units = 1
+overflow-checks = true
+opt-level = {opt_level}
+debug = false
+"
+            ),
+            Profile::Debug if self.optimize_size => format!(
+                "[profile.dev]
+opt-level = {opt_level}
+"
+            ),
+            Profile::Debug => String::new(),
+        }
+    }
+
+    /// Returns the settings changing the built wasm of a package of the same content
+    pub fn artifact_key(&self) -> String {
+        format!(
+            "{}/{}{}",
+            self.target,
+            self.profile,
+            if self.optimize_size { "/optimized" } else { "" }
+        )
+    }
+}
+
+/// Computes a hash of the content of a generated package, used to find a previous successful
+/// build of the same package
+///
+/// # Arguments
+///
+/// * `package_dir` - A reference to the directory holding the `Cargo.toml` of the package
+/// * `target` - A string slice that holds the settings the package is built with, such as
+///   the target triple, the profile and the version of the toolchain
+///
+/// # Returns
+///
+/// * `Result<String, Error>` - Result containing the hash in hexadecimal, or an error if
+///   the files of the package cannot be read
+///
+pub fn get_package_hash(package_dir: &Path, target: &str) -> Result<String, Error> {
+    let mut files = vec![package_dir.join("Cargo.toml")];
+
+    for file in [".cargo/config.toml", "Cargo.lock"] {
+        let file = package_dir.join(file);
+        if file.exists() {
+            files.push(file);
+        }
+    }
+
+    for entry in fs::read_dir(package_dir.join("src"))? {
+        files.push(entry?.path());
+    }
+
+    files.sort();
+
+    let mut hasher = FnvHasher::default();
+    hasher.write(target.as_bytes());
+
+    for file in files.iter() {
+        let content = fs::read(file)?;
+        let name = file.strip_prefix(package_dir).unwrap_or(file);
+
+        hasher.write(name.to_string_lossy().as_bytes());
+        hasher.write(&(content.len() as u64).to_le_bytes());
+        hasher.write(&content);
+    }
+
+    Ok(format!("{:016x}", hasher.finish()))
+}
+
+/// Returns the version of the Rust toolchain building the given package, as reported by
+/// `rustc -vV`, so that the wasm built by another toolchain is not reused
+///
+/// # Arguments
+///
+/// * `package_dir` - A reference to the directory of the package, where a toolchain
+///   override of rustup applies
+///
+/// # Returns
+///
+/// * `Result<String, Error>` - Result containing the version along with the commit and the
+///   LLVM version of the compiler, or an error if rustc cannot be run
+///
+pub fn get_toolchain_version(package_dir: &Path) -> Result<String, Error> {
+    let output = Command::new(std::env::var_os("RUSTC").unwrap_or_else(|| "rustc".into()))
+        .current_dir(package_dir)
+        .arg("-vV")
+        .output()?;
+
+    if !output.status.success() {
+        return Err(Error::msg(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
@@ -6,20 +6,43 @@ use starlark::eval::ReturnFileLoader;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
 use super::*;
 
 const COMMON: &str = include_str!("../boilerplate/src/common.rs");
+const ERRORS: &str = include_str!("../boilerplate/src/errors.rs");
 const LIB: &str = include_str!("../boilerplate/src/lib.rs");
 const TRAIT: &str = include_str!("../boilerplate/src/traits.rs");
 const MACROS: &str = include_str!("../boilerplate/src/macros.rs");
 const CARGO: &str = include_str!("../boilerplate/Cargo.toml");
 
+/// Prefix of the modules of the standard library in `load()`
+pub(crate) const STD_PREFIX: &str = "@std//";
+
+/// Modules of the standard library compiled into the composer, by file name
+const STD_MODULES: &[(&str, &str)] = &[
+    ("common.echo", include_str!("../std/common.echo")),
+    ("openwhisk.echo", include_str!("../std/openwhisk.echo")),
+    ("polkadot.echo", include_str!("../std/polkadot.echo")),
+];
+
 #[derive(Debug, ProvidesStaticType, Default)]
 pub struct Composer {
     pub config_files: Vec<String>,
     pub workflows: RefCell<Vec<Workflow>>,
     pub custom_types: RefCell<HashMap<String, String>>,
+    /// Tasks created by the config files, whether they are added to a workflow or not
+    pub tasks: RefCell<Vec<Task>>,
+    /// Lint rules suppressed by the config files
+    pub lint_suppressions: RefCell<Vec<Suppression>>,
+    /// Defaults given by `defaults()` to the tasks of each kind, by kind
+    pub task_defaults: RefCell<HashMap<String, TaskDefaults>>,
+    /// Evaluated config files by canonical path, so that a file loaded several times declares
+    /// its workflows once
+    modules: RefCell<HashMap<PathBuf, FrozenModule>>,
 }
 
 impl Composer {
@@ -49,55 +72,176 @@ impl Composer {
     /// * `name` - Name of the workflow to be added
     /// * `version` - Version of the workflow
     /// * `tasks` - HashMap of tasks associated with the workflow
-    /// * `custom_types` - Optional vector of custom types names that are created within config
-    ///   for the workflow.
+    /// * `timeout_ms` - Optional maximum time in milliseconds the workflow may run
     ///
     /// # Returns
     ///
     /// * `Result<(), Error>` - Result indicating success if the workflow is added successfully,
-    ///   or an error if the workflow name is empty or if there is a duplicate workflow name.
+    ///   or an error if the name is empty or not a valid workflow name, if the version is not
+    ///   a semantic version, or if a workflow with the same name and version already exists.
     ///
     pub fn add_workflow(
         &self,
         name: String,
         version: String,
         tasks: HashMap<String, Task>,
+        timeout_ms: Option<u64>,
     ) -> Result<(), Error> {
-        for workflow in self.workflows.borrow().iter() {
-            if workflow.name == name {
-                return Err(Error::msg("Workflows should not have same name"));
-            }
-        }
         if name.is_empty() {
-            Err(Error::msg("Workflow name should not be empty"))
-        } else {
-            self.workflows.borrow_mut().push(Workflow {
-                name,
+            return Err(Error::msg("Workflow name should not be empty"));
+        }
+
+        validate_workflow_name(&name)?;
+
+        semver::Version::parse(&version).map_err(|err| {
+            anyhow!(
+                "Invalid version {:?} of workflow {}, expected a semantic version such as \
+                0.0.1: {}",
                 version,
-                tasks,
-            });
-            Ok(())
+                name,
+                err
+            )
+        })?;
+
+        let workflow = Workflow {
+            name,
+            version,
+            tasks,
+            timeout_ms,
+        };
+
+        for existing in self.workflows.borrow().iter() {
+            if existing.name == workflow.name && existing.version == workflow.version {
+                return Err(anyhow!(
+                    "Workflows should not have same name and version, found {} {} twice",
+                    workflow.name,
+                    workflow.version
+                ));
+            }
+
+            if get_package_name(existing) == get_package_name(&workflow) {
+                return Err(anyhow!(
+                    "Workflows {} {} and {} {} generate the same package {}, rename one of them",
+                    existing.name,
+                    existing.version,
+                    workflow.name,
+                    workflow.version,
+                    get_package_name(&workflow)
+                ));
+            }
         }
+
+        self.workflows.borrow_mut().push(workflow);
+        Ok(())
     }
 
-    pub fn build(verbose: bool, temp_dir: &Path) -> Result<(), Error> {
-        if verbose {
+    /// Builds the generated package of a workflow into wasm.
+    /// The compiler errors are collected from the JSON messages of cargo and traced back to
+    /// the task and argument of the workflow which produced the offending code.
+    ///
+    /// # Arguments
+    ///
+    /// * `options` - A reference to the build options, cargo builds into the shared target
+    ///   directory of the cache
+    /// * `temp_dir` - A reference to the directory holding the generated package
+    /// * `workflow` - A reference to the workflow the package was generated for
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success if the package is built, or an error
+    ///   listing the compiler errors if cargo fails
+    ///
+    pub fn build(
+        options: &BuildOptions,
+        temp_dir: &Path,
+        workflow: &Workflow,
+    ) -> Result<(), Error> {
+        let package_dir = temp_dir.join("boilerplate");
+
+        if let (true, false, Some(triple)) =
+            (options.verbose, options.offline, options.target.triple())
+        {
             Command::new("rustup")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["target", "add", "wasm32-wasi"])
+                .current_dir(&package_dir)
+                .args(["target", "add", triple])
                 .status()?;
+        }
 
-            Command::new("cargo")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["build", "--release", "--target", "wasm32-wasi"])
-                .status()?;
+        let mut command = Command::new("cargo");
+        command
+            .current_dir(&package_dir)
+            .env("CARGO_TARGET_DIR", options.target_dir())
+            .args(["build", "--message-format=json"]);
+
+        if options.profile == Profile::Release {
+            command.arg("--release");
+        }
+
+        if let Some(triple) = options.target.triple() {
+            command.args(["--target", triple]);
+        }
+
+        if options.offline {
+            command.arg("--offline");
+        }
+
+        if options.verbose {
+            command.stderr(Stdio::inherit());
         } else {
-            Command::new("cargo")
-                .current_dir(temp_dir.join("boilerplate"))
-                .args(["build", "--release", "--target", "wasm32-wasi", "--quiet"])
-                .status()?;
+            command.arg("--quiet");
         }
-        Ok(())
+
+        let output = command.output()?;
+
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let diagnostics = get_compiler_errors(&String::from_utf8_lossy(&output.stdout));
+
+        if diagnostics.is_empty() {
+            return Err(anyhow!(
+                "cargo exited with {}\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim_end()
+            ));
+        }
+
+        let types_rs = fs::read_to_string(package_dir.join("src/types.rs")).unwrap_or_default();
+
+        let errors: Vec<String> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let source = diagnostic.line.and_then(|line| {
+                    get_generated_code_source(workflow, &types_rs, line)
+                });
+
+                let span = diagnostic.line.and_then(|line| {
+                    get_generated_code_span(workflow, &types_rs, line)
+                });
+
+                match source {
+                    Some(source) => format!(
+                        "{}\n{}",
+                        with_span(
+                            format!(
+                                "error in the code generated for {}: {}",
+                                source, diagnostic.message
+                            ),
+                            span.as_ref()
+                        ),
+                        diagnostic.rendered.trim_end()
+                    ),
+                    None => diagnostic.rendered.trim_end().to_string(),
+                }
+            })
+            .collect();
+
+        Err(anyhow!(
+            "cargo exited with {}\n{}",
+            output.status,
+            errors.join("\n")
+        ))
     }
 
     fn copy_boilerplate(
@@ -105,6 +249,8 @@ impl Composer {
         types_rs: String,
         workflow_name: String,
         workflow: &Workflow,
+        options: &BuildOptions,
+        lock: Option<&LockFile>,
     ) -> Result<PathBuf, Error> {
         let temp_dir = temp_dir.join(workflow_name);
         let curr = temp_dir.join("boilerplate");
@@ -116,11 +262,13 @@ impl Composer {
 
         std::fs::write(temp_path, COMMON)?;
 
+        let temp_path = src_curr.as_path().join("errors.rs");
+        std::fs::write(temp_path, ERRORS)?;
+
         let temp_path = src_curr.as_path().join("lib.rs");
         std::fs::write(temp_path.clone(), LIB)?;
 
         let mut lib = OpenOptions::new()
-            .write(true)
             .append(true)
             .open(temp_path)?;
 
@@ -136,52 +284,143 @@ impl Composer {
         let temp_path = src_curr.as_path().join("macros.rs");
         std::fs::write(temp_path, MACROS)?;
 
+        // The packages share a cargo target directory, they are named after the workflow so
+        // that their wasm do not overwrite each other
         let cargo_path = curr.join("Cargo.toml");
-        std::fs::write(cargo_path.clone(), CARGO)?;
+        std::fs::write(
+            cargo_path.clone(),
+            CARGO.replacen(
+                "name = \"boilerplate\"",
+                &format!("name = \"{}\"", get_package_name(workflow)),
+                1,
+            ),
+        )?;
 
         let mut cargo_toml = OpenOptions::new()
-            .write(true)
             .append(true)
             .open(cargo_path)?;
 
-        let dependencies = generate_cargo_toml_dependencies(workflow);
+        let dependencies = generate_cargo_toml_dependencies(workflow, &options.patches);
         writeln!(cargo_toml, "{dependencies}")
             .expect("could not able to add dependencies to the Cargo.toml");
+        writeln!(cargo_toml, "{}", options.cargo_profile())
+            .expect("could not able to add the profile to the Cargo.toml");
+
+        if let Some(vendor_dir) = &options.vendor_dir {
+            std::fs::create_dir_all(curr.join(".cargo"))?;
+            std::fs::write(
+                curr.join(".cargo/config.toml"),
+                generate_cargo_config(workflow, vendor_dir),
+            )?;
+        }
+
+        // Cargo keeps the pinned versions of the dependencies the package uses and drops
+        // the others
+        if let Some(lock) = lock {
+            std::fs::write(curr.join("Cargo.lock"), lock.to_string())?;
+        }
 
         Ok(temp_dir)
     }
 }
 
 impl Composer {
+    /// Evaluates the given config file of the package, along with the files it loads, adding
+    /// the workflows it declares to the composer
+    ///
+    /// # Arguments
+    ///
+    /// * `module` - A string slice that holds the path of the config file, relative to the base
+    ///   of the package, or to the root of the workspace when it starts with `//`
+    /// * `files` - A reference to the source files of the package
+    ///
+    /// # Returns
+    ///
+    /// * `Result<FrozenModule, Error>` - Result containing the evaluated module, or an error if
+    ///   a file cannot be found or evaluated, if files load each other in a cycle, or if no
+    ///   workflow is declared
+    ///
     pub fn compile(&self, module: &str, files: &SourceFiles) -> Result<FrozenModule, Error> {
-        let ast: AstModule = AstModule::parse_file(
-            files
-                .files()
-                .get(&PathBuf::from(format!(
-                    "{}/{}",
-                    files.base().display(),
-                    module
-                )))
-                .ok_or_else(|| {
-                    Error::msg(format!(
-                        "FileNotFound at {}/{}",
-                        files.base().display(),
-                        module
-                    ))
-                })?,
-            &Dialect::Extended,
-        )
+        let module = self.compile_module(&files.resolve(module, None)?, files, &mut Vec::new())?;
+
+        if self.workflows.borrow().is_empty() {
+            return Err(Error::msg("Empty workflow detected!!!"));
+        }
+
+        Ok(module)
+    }
+
+    /// Evaluates a config file once, after the files it loads
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A reference to the path of the config file
+    /// * `files` - A reference to the source files of the package
+    /// * `loading` - A mutable reference to the canonical paths of the files being loaded,
+    ///   from the entry file to the file loading this one
+    ///
+    fn compile_module(
+        &self,
+        path: &Path,
+        files: &SourceFiles,
+        loading: &mut Vec<PathBuf>,
+    ) -> Result<FrozenModule, Error> {
+        let std_module = path.to_str().and_then(|path| path.strip_prefix(STD_PREFIX));
+
+        let path = match std_module {
+            Some(_) => path.to_path_buf(),
+            None => fs::canonicalize(path)
+                .map_err(|_| Error::msg(format!("FileNotFound at {}", path.display())))?,
+        };
+
+        if let Some(module) = self.modules.borrow().get(&path) {
+            return Ok(module.clone());
+        }
+
+        if let Some(start) = loading.iter().position(|loaded| *loaded == path) {
+            let root = fs::canonicalize(files.root()).unwrap_or_else(|_| files.root().clone());
+
+            let chain: Vec<String> = loading[start..]
+                .iter()
+                .chain([&path])
+                .map(|loaded| loaded.strip_prefix(&root).unwrap_or(loaded).display().to_string())
+                .collect();
+
+            return Err(Error::msg(format!(
+                "Load cycle detected: {}",
+                chain.join(" -> ")
+            )));
+        }
+
+        let ast: AstModule = match std_module {
+            Some(name) => AstModule::parse(
+                &path.display().to_string(),
+                get_std_module(name)?.to_string(),
+                &Dialect::Extended,
+            ),
+            None => AstModule::parse_file(&path, &Dialect::Extended),
+        }
         .map_err(|err| Error::msg(format!("Error parsing file: {}", err)))?;
 
+        loading.push(path.clone());
+
         let mut loads = Vec::new();
 
         for load in ast.loads() {
+            let module_path = if load.module_id.starts_with(STD_PREFIX) {
+                PathBuf::from(load.module_id)
+            } else {
+                files.resolve(load.module_id, Some(&path))?
+            };
+
             loads.push((
                 load.module_id.to_owned(),
-                Self::compile(self, load.module_id, files)?,
+                self.compile_module(&module_path, files, loading)?,
             ));
         }
 
+        loading.pop();
+
         let modules = loads.iter().map(|(a, b)| (a.as_str(), b)).collect();
         let loader = ReturnFileLoader { modules: &modules };
 
@@ -220,88 +459,429 @@ impl Composer {
             result.map_err(|err| Error::msg(format!("Evaluation error: {}", err)))?;
         }
 
-        if self.workflows.borrow().is_empty(){
-            return Err(Error::msg("Empty workflow detected!!!"));
-        }
-        Ok(module.freeze()?)
+        let module = module.freeze()?;
+        self.modules.borrow_mut().insert(path, module.clone());
+
+        Ok(module)
     }
 
-    pub fn build_directory(
+    /// Builds the wasm of every workflow of the composer into the output directory.
+    /// At most `options.jobs` workflows are built at the same time. Unless `options.keep_going`
+    /// is set, the workflows which have not started yet are skipped once a workflow fails.
+    /// The dependencies are pinned by `options.lock_file` when it exists, otherwise it is
+    /// created once all the workflows are built. An existing lock file is only refreshed by
+    /// `update_lock_file`, the workflows whose dependencies it misses are reported instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `build_path` - A reference to the directory where the packages are generated
+    /// * `out_path` - A reference to the directory where the wasm are written
+    /// * `options` - A reference to the build options
+    /// * `on_progress` - A function called with the name of the workflow, its build stage and
+    ///   the time elapsed since its build started, whenever the stage changes
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<BuildReport>, Error>` - Result containing the report of each workflow in
+    ///   the order of the workflows, or an error if the builds cannot be started or the lock
+    ///   file cannot be read or written
+    ///
+    pub fn build_directory<F>(
         &self,
         build_path: &Path,
         out_path: &Path,
-        quiet: bool,
-    ) -> anyhow::Result<(), Error> {
+        options: &BuildOptions,
+        on_progress: F,
+    ) -> anyhow::Result<Vec<BuildReport>, Error>
+    where
+        F: Fn(&str, BuildStage, Duration) + Sync,
+    {
         let composer_custom_types = self.custom_types.take();
 
-        let workflows = self.workflows.take();
+        let workflows: Vec<Workflow> = self
+            .workflows
+            .take()
+            .into_iter()
+            .filter(|workflow| !workflow.tasks.is_empty())
+            .collect();
+
+        for workflow in workflows.iter() {
+            on_progress(
+                &format!("{}_{}", workflow.name, workflow.version),
+                BuildStage::Queued,
+                Duration::ZERO,
+            );
+        }
+
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(options.jobs())
+            .build()?;
+
+        let lock = match &options.lock_file {
+            Some(lock_file) => LockFile::read(lock_file)?,
+            None => None,
+        };
+
+        let failed = AtomicBool::new(false);
+
+        let (mut reports, cargo_locks): (Vec<BuildReport>, Vec<Option<_>>) = pool.install(|| {
+            workflows
+                .par_iter()
+                .map(|workflow| {
+                    let workflow_name = format!("{}_{}", workflow.name, workflow.version);
+                    let started = Instant::now();
+
+                    if failed.load(Ordering::SeqCst) && !options.keep_going {
+                        on_progress(&workflow_name, BuildStage::Skipped, Duration::ZERO);
+
+                        let report = BuildReport {
+                            workflow: workflow_name,
+                            stage: BuildStage::Skipped,
+                            elapsed: Duration::ZERO,
+                            size: None,
+                            error: None,
+                            lock_outdated: false,
+                        };
+
+                        return (report, None);
+                    }
+
+                    let result = Self::build_workflow(
+                        workflow,
+                        &composer_custom_types,
+                        build_path,
+                        out_path,
+                        options,
+                        lock.as_ref(),
+                        |stage| on_progress(&workflow_name, stage, started.elapsed()),
+                    );
+
+                    let (stage, size, cargo_lock, error) = match result {
+                        std::result::Result::Ok((stage, size, cargo_lock)) => {
+                            (stage, Some(size), Some(cargo_lock), None)
+                        }
+                        Err(error) => {
+                            failed.store(true, Ordering::SeqCst);
+                            (BuildStage::Failed, None, None, Some(error))
+                        }
+                    };
+
+                    on_progress(&workflow_name, stage, started.elapsed());
+
+                    let report = BuildReport {
+                        workflow: workflow_name,
+                        stage,
+                        elapsed: started.elapsed(),
+                        size,
+                        error,
+                        lock_outdated: false,
+                    };
+
+                    (report, cargo_lock)
+                })
+                .unzip()
+        });
+
+        match (&lock, &options.lock_file) {
+            (Some(lock), _) => {
+                for ((report, workflow), cargo_lock) in
+                    reports.iter_mut().zip(workflows.iter()).zip(cargo_locks.iter())
+                {
+                    if let Some(cargo_lock) = cargo_lock {
+                        report.lock_outdated = !lock.pins(cargo_lock, &get_package_name(workflow));
+                    }
+                }
+            }
+            // The lock file is only created once every workflow is built, so that it does not
+            // miss the dependencies of the workflows which failed
+            (None, Some(lock_file)) => {
+                if let Some(cargo_locks) = cargo_locks.into_iter().collect::<Option<Vec<_>>>() {
+                    let mut created = LockFile::default();
+
+                    for (workflow, cargo_lock) in workflows.iter().zip(cargo_locks.iter()) {
+                        created.merge(cargo_lock, &get_package_name(workflow));
+                    }
 
-        let results: Vec<Result<(), Error>> = workflows
-            .par_iter()
-            .enumerate()
-            .map(|workflow: (usize, &Workflow)| {
-                if workflow.1.tasks.is_empty() {
-                    return Ok(());
+                    fs::write(lock_file, created.to_string())?;
                 }
+            }
+            (None, None) => {}
+        }
 
-                let workflow_name = format!("{}_{}", workflow.1.name, workflow.1.version);
-
-                let types_rs =
-                    generate_types_rs_file_code(&workflows[workflow.0], &composer_custom_types)
-                        .map_err(|err| {
-                            anyhow!(
-                                "{}: Failed to generate types.rs file: {}",
-                                workflow.1.name,
-                                err
-                            )
-                        })?;
-
-                let temp_dir =
-                    Self::copy_boilerplate(build_path, types_rs, workflow_name.clone(), workflow.1)
-                        .map_err(|err| {
-                            anyhow!("{}: Failed to copy boilerplate: {}", workflow.1.name, err)
-                        })?;
-
-                Self::build(quiet, &temp_dir)
-                    .map_err(|err| anyhow!("{}: Failed to build: {}", workflow.1.name, err))?;
-
-                let wasm_path = format!(
-                    "{}/boilerplate/target/wasm32-wasi/release/boilerplate.wasm",
-                    temp_dir.display()
-                );
-
-                fs::create_dir_all(out_path.join("output")).map_err(|err| {
-                    anyhow!(
-                        "{}: Failed to create output directory: {}",
-                        workflow.1.name,
-                        err
-                    )
-                })?;
+        Ok(reports)
+    }
+
+    /// Generates the package of a workflow and builds it, or reuses the wasm of a previous
+    /// build of the same package, then copies the wasm into the output directory.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(BuildStage, u64, LockFile), Error>` - Result containing `Done` if the package was
+    ///   built or `Cached` if the wasm of a previous build was reused, along with the size of
+    ///   the wasm in bytes and the `Cargo.lock` of the package, or an error if any step fails
+    ///
+    fn build_workflow(
+        workflow: &Workflow,
+        custom_types: &HashMap<String, String>,
+        build_path: &Path,
+        out_path: &Path,
+        options: &BuildOptions,
+        lock: Option<&LockFile>,
+        on_stage: impl Fn(BuildStage),
+    ) -> Result<(BuildStage, u64, LockFile), Error> {
+        on_stage(BuildStage::Generating);
+
+        // The timeouts would silently never expire, as the target provides no clock
+        if options.target == Target::WasmUnknown && workflow.has_timeouts() {
+            return Err(anyhow!(
+                "{}: Timeouts are not supported on {}, which provides no clock, build for {} instead",
+                workflow.name,
+                Target::WasmUnknown,
+                Target::Wasip1
+            ));
+        }
+
+        let workflow_name = format!("{}_{}", workflow.name, workflow.version);
 
-                fs::copy(
-                    wasm_path,
-                    out_path.join(format!("output/{workflow_name}.wasm")),
+        let types_rs = generate_types_rs_file_code(workflow, custom_types).map_err(|err| {
+            anyhow!("{}: Failed to generate types.rs file: {}", workflow.name, err)
+        })?;
+
+        let temp_dir =
+            Self::copy_boilerplate(build_path, types_rs, workflow_name.clone(), workflow, options, lock)
+                .map_err(|err| anyhow!("{}: Failed to copy boilerplate: {}", workflow.name, err))?;
+
+        let package_dir = temp_dir.join("boilerplate");
+        let hash = get_toolchain_version(&package_dir)
+            .and_then(|toolchain| {
+                get_package_hash(
+                    &package_dir,
+                    &format!("{}\n{}", options.artifact_key(), toolchain),
+                )
+            })
+            .map_err(|err| anyhow!("{}: Failed to hash the package: {}", workflow.name, err))?;
+
+        let wasm_path = options.cached_wasm(&hash);
+        let lock_path = options.cached_lock(&hash);
+
+        // A package with the same content was built before, its wasm and the dependencies it
+        // was built with are reused
+        let stage = if wasm_path.exists() && lock_path.exists() {
+            BuildStage::Cached
+        } else {
+            on_stage(BuildStage::Compiling);
+
+            Self::build(options, &temp_dir, workflow).map_err(|err| {
+                anyhow!(
+                    "{}: Failed to build: {}\nThe generated package is kept at {}",
+                    workflow.name,
+                    err,
+                    temp_dir.join("boilerplate").display()
                 )
-                .map_err(|err| anyhow!("{}: Failed to copy wasm: {}", workflow.1.name, err))?;
+            })?;
+
+            let built_wasm = options.built_artifact(&get_package_name(workflow));
+
+            fs::create_dir_all(options.cache_dir.join("wasm"))
+                .and_then(|_| fs::copy(temp_dir.join("boilerplate/Cargo.lock"), &lock_path))
+                .and_then(|_| fs::copy(built_wasm, &wasm_path))
+                .map_err(|err| anyhow!("{}: Failed to cache wasm: {}", workflow.name, err))?;
+
+            if options.optimize_size && options.target != Target::Native {
+                Self::optimize_size(&wasm_path).map_err(|err| {
+                    // The unoptimized wasm must not be reused as an optimized one
+                    let _ = fs::remove_file(&wasm_path);
+                    anyhow!("{}: Failed to optimize wasm: {}", workflow.name, err)
+                })?;
+            }
+
+            BuildStage::Done
+        };
+
+        fs::create_dir_all(out_path.join("output")).map_err(|err| {
+            anyhow!(
+                "{}: Failed to create output directory: {}",
+                workflow.name,
+                err
+            )
+        })?;
+
+        let size = fs::copy(
+            &wasm_path,
+            out_path.join(format!(
+                "output/{workflow_name}.{}",
+                options.target.extension()
+            )),
+        )
+        .map_err(|err| anyhow!("{}: Failed to copy wasm: {}", workflow.name, err))?;
+
+        let cargo_lock = fs::read_to_string(&lock_path)
+            .map_err(Error::from)
+            .and_then(|cargo_lock| cargo_lock.parse())
+            .map_err(|err| anyhow!("{}: Failed to read cached lock: {}", workflow.name, err))?;
+
+        if !options.keep_build {
+            fs::remove_dir_all(temp_dir)
+                .map_err(|err| anyhow!("{}: Failed to remove temp dir: {}", workflow.name, err))?;
+        }
+
+        Ok((stage, size, cargo_lock))
+    }
+
+    /// Reads the `Cargo.lock` of a generated package, resolving the dependencies first if it
+    /// has none. Only used when updating the lock file, a build reads the `Cargo.lock` stored
+    /// along with the cached wasm
+    fn read_cargo_lock(options: &BuildOptions, package_dir: &Path) -> Result<LockFile, Error> {
+        let cargo_lock = package_dir.join("Cargo.lock");
+
+        if !cargo_lock.exists() {
+            let mut command = Command::new("cargo");
+            command.current_dir(package_dir).arg("generate-lockfile");
+
+            if options.offline {
+                command.arg("--offline");
+            }
 
+            if !options.verbose {
+                command.arg("--quiet");
+            }
+
+            let output = command.stdout(Stdio::null()).output()?;
+
+            if !output.status.success() {
+                return Err(anyhow!(
+                    "cargo exited with {}\n{}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim_end()
+                ));
+            }
+        }
+
+        fs::read_to_string(cargo_lock)?.parse()
+    }
+
+    /// Resolves the dependencies of the generated packages of every workflow of the composer
+    /// to their latest compatible versions, ignoring the current lock file.
+    ///
+    /// # Arguments
+    ///
+    /// * `build_path` - A reference to the directory where the packages are generated
+    /// * `options` - A reference to the build options
+    ///
+    /// # Returns
+    ///
+    /// * `Result<LockFile, Error>` - Result containing the dependencies of all the packages,
+    ///   or an error if the packages cannot be generated or cargo fails to resolve them
+    ///
+    pub fn update_lock_file(
+        &self,
+        build_path: &Path,
+        options: &BuildOptions,
+    ) -> anyhow::Result<LockFile, Error> {
+        let custom_types = self.custom_types.take();
+        let mut lock = LockFile::default();
+
+        for workflow in self.workflows.take().iter() {
+            if workflow.tasks.is_empty() {
+                continue;
+            }
+
+            let workflow_name = format!("{}_{}", workflow.name, workflow.version);
+
+            let types_rs = generate_types_rs_file_code(workflow, &custom_types).map_err(|err| {
+                anyhow!("{}: Failed to generate types.rs file: {}", workflow.name, err)
+            })?;
+
+            let temp_dir =
+                Self::copy_boilerplate(build_path, types_rs, workflow_name, workflow, options, None)
+                    .map_err(|err| {
+                        anyhow!("{}: Failed to copy boilerplate: {}", workflow.name, err)
+                    })?;
+
+            let cargo_lock = Self::read_cargo_lock(options, &temp_dir.join("boilerplate"))
+                .map_err(|err| {
+                    anyhow!("{}: Failed to lock dependencies: {}", workflow.name, err)
+                })?;
+
+            lock.merge(&cargo_lock, &get_package_name(workflow));
+
+            if !options.keep_build {
                 fs::remove_dir_all(temp_dir).map_err(|err| {
-                    anyhow!("{}: Failed to remove temp dir: {}", workflow.1.name, err)
+                    anyhow!("{}: Failed to remove temp dir: {}", workflow.name, err)
                 })?;
+            }
+        }
 
-                Ok(())
-            })
-            .filter(|result| result.is_err())
-            .collect::<Vec<_>>()
-            .into_iter()
-            .collect();
+        Ok(lock)
+    }
 
-        if !results.is_empty() {
-            return Err(Error::msg(format!(
-                "Failed to build the following workflows: {:?}",
-                results
-            )));
+    /// Shrinks the given wasm in place with `wasm-opt`
+    ///
+    /// # Arguments
+    ///
+    /// * `wasm_path` - A reference to the path of the wasm to be optimized
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success if the wasm is optimized, or an error
+    ///   if `wasm-opt` is not installed or fails
+    ///
+    fn optimize_size(wasm_path: &Path) -> Result<(), Error> {
+        let output = Command::new("wasm-opt")
+            .arg("-Oz")
+            .arg(wasm_path)
+            .arg("-o")
+            .arg(wasm_path)
+            .output()
+            .map_err(|err| match err.kind() {
+                ErrorKind::NotFound => anyhow!(
+                    "wasm-opt was not found, install binaryen or build without --optimize-size"
+                ),
+                _ => anyhow!("Failed to run wasm-opt: {}", err),
+            })?;
+
+        if !output.status.success() {
+            return Err(anyhow!(
+                "wasm-opt exited with {}\n{}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            ));
         }
 
         Ok(())
     }
 }
+
+/// Returns the source of the module of the standard library with the given file name
+fn get_std_module(name: &str) -> Result<&'static str, Error> {
+    STD_MODULES
+        .iter()
+        .find(|(module, _)| *module == name)
+        .map(|(_, source)| *source)
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "Module {} is not part of the standard library, expected one of: {}",
+                name,
+                STD_MODULES
+                    .iter()
+                    .map(|(module, _)| format!("{STD_PREFIX}{module}"))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+/// Returns the name of the generated package of the workflow, which is also the name of
+/// its wasm in the cargo target directory
+fn get_package_name(workflow: &Workflow) -> String {
+    format!("boilerplate_{}_{}", workflow.name, workflow.version)
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
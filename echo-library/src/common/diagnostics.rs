@@ -0,0 +1,167 @@
+use super::*;
+use regex::Regex;
+
+/// Error reported by rustc while building the generated package of a workflow
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub rendered: String,
+    /// Line of the generated `types.rs` the error points to
+    pub line: Option<usize>,
+}
+
+/// Extracts the compiler errors from the output of `cargo build --message-format=json`.
+/// Errors raised inside the expansion of a boilerplate macro are traced back to the line of
+/// `types.rs` where the macro is invoked.
+///
+/// # Arguments
+///
+/// * `messages` - A string slice that holds the JSON messages, one per line
+///
+/// # Returns
+///
+/// * `Vec<Diagnostic>` - A vector containing the compiler errors in the order they were reported
+///
+pub fn get_compiler_errors(messages: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    for message in messages.lines() {
+        let message: serde_json::Value = match serde_json::from_str(message) {
+            Ok(message) => message,
+            Err(_) => continue,
+        };
+
+        if message["reason"] != "compiler-message" || message["message"]["level"] != "error" {
+            continue;
+        }
+
+        let message = &message["message"];
+
+        let line = message["spans"]
+            .as_array()
+            .and_then(|spans| {
+                spans
+                    .iter()
+                    .find(|span| span["is_primary"] == true)
+                    .or_else(|| spans.first())
+            })
+            .and_then(get_types_rs_line);
+
+        diagnostics.push(Diagnostic {
+            message: message["message"].as_str().unwrap_or_default().to_string(),
+            rendered: message["rendered"].as_str().unwrap_or_default().to_string(),
+            line,
+        });
+    }
+
+    diagnostics
+}
+
+fn get_types_rs_line(span: &serde_json::Value) -> Option<usize> {
+    let mut span = span;
+
+    loop {
+        if span["file_name"]
+            .as_str()
+            .is_some_and(|file_name| file_name.ends_with("types.rs"))
+        {
+            return span["line_start"].as_u64().map(|line| line as usize);
+        }
+
+        span = span.get("expansion")?.get("span")?;
+    }
+}
+
+/// Finds the task and the argument of the workflow which produced the given line of the
+/// generated `types.rs`.
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow the `types.rs` was generated for
+/// * `types_rs` - A string slice that holds the content of the generated `types.rs`
+/// * `line` - The line number (starting at 1) reported by the compiler
+///
+/// # Returns
+///
+/// * `Option<String>` - An option containing a description of the task and the argument, or
+///   None if the line cannot be related to a task
+///
+pub fn get_generated_code_source(workflow: &Workflow, types_rs: &str, line: usize) -> Option<String> {
+    match find_generated_code_source(workflow, types_rs, line)? {
+        (task_name, Some(argument)) => Some(format!("task {}, argument {}", task_name, argument)),
+        (task_name, None) => Some(format!("task {}", task_name)),
+    }
+}
+
+/// Finds the call of the config file which created the argument, or else the task, that
+/// produced the given line of the generated `types.rs`.
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow the `types.rs` was generated for
+/// * `types_rs` - A string slice that holds the content of the generated `types.rs`
+/// * `line` - The line number (starting at 1) reported by the compiler
+///
+/// # Returns
+///
+/// * `Option<Span>` - An option containing the span of the argument or the task, or None if
+///   the line cannot be related to a task or the task was not created by a config file
+///
+pub fn get_generated_code_span(workflow: &Workflow, types_rs: &str, line: usize) -> Option<Span> {
+    let (task_name, argument) = find_generated_code_source(workflow, types_rs, line)?;
+    let workflow = workflow.with_compensations().ok()?;
+    let task = workflow.tasks.get(&task_name)?;
+
+    argument
+        .and_then(|argument| {
+            task.input_arguments
+                .iter()
+                .find(|input| input.name == argument)
+                .and_then(|input| input.span.clone())
+        })
+        .or_else(|| task.span.clone())
+}
+
+/// Returns the name of the task and of the argument which produced the given line of the
+/// generated `types.rs`
+fn find_generated_code_source(
+    workflow: &Workflow,
+    types_rs: &str,
+    line: usize,
+) -> Option<(String, Option<String>)> {
+    let lines: Vec<&str> = types_rs.lines().collect();
+    let offending_line = *lines.get(line.checked_sub(1)?)?;
+    let workflow = workflow.with_compensations().ok()?;
+
+    let mut arguments = Vec::new();
+
+    for task in workflow.tasks.values() {
+        for input in task.input_arguments.iter() {
+            let argument = Regex::new(&format!(r"\b{}(_fn)?\b", regex::escape(&input.name))).ok()?;
+
+            if argument.is_match(offending_line) {
+                arguments.push((task.action_name.clone(), input.name.clone()));
+            }
+        }
+    }
+
+    // The generated code refers to a task by its name in PascalCase, search upwards for the
+    // closest one as a macro invocation may span several lines
+    let task = lines[..line].iter().rev().find_map(|line| {
+        workflow.tasks.keys().find(|task_name| {
+            Regex::new(&format!(r"\b{}(Input)?\b", task_name.to_case(Case::Pascal)))
+                .is_ok_and(|pattern| pattern.is_match(line))
+        })
+    });
+
+    let argument = arguments
+        .iter()
+        .find(|(task_name, _)| Some(task_name) == task)
+        .or_else(|| arguments.first());
+
+    match (task, argument) {
+        (_, Some((task_name, argument))) => Some((task_name.clone(), Some(argument.clone()))),
+        (Some(task_name), None) => Some((task_name.clone(), None)),
+        (None, None) => None,
+    }
+}
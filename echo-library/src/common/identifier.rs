@@ -0,0 +1,313 @@
+use super::*;
+
+/// Keywords of Rust, which are escaped as raw identifiers in the generated code
+const RUST_KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// Keywords which cannot be raw identifiers
+const PATH_KEYWORDS: &[&str] = &["crate", "self", "super", "Self"];
+
+/// Types the generated code declares or uses, which a task type must not shadow
+const RESERVED_TYPES: &[&str] = &[
+    "Box",
+    "Compensation",
+    "Constraint",
+    "Execute",
+    "HashMap",
+    "Input",
+    "Option",
+    "Output",
+    "Phase",
+    "Result",
+    "String",
+    "Value",
+    "Vec",
+    "WorkflowError",
+    "WorkflowGraph",
+];
+
+/// Variables of the generated `main`, which a task variable must not shadow
+const RESERVED_VARIABLES: &[&str] = &["args", "input", "result", "workflow"];
+
+/// Returns the name as an identifier of the generated code, escaped as a raw identifier if
+/// it is a Rust keyword
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds a valid name of an argument or a task variable
+///
+/// # Returns
+///
+/// * `String` - The name, prefixed with `r#` if it is a keyword
+///
+pub fn get_identifier(name: &str) -> String {
+    if RUST_KEYWORDS.contains(&name) {
+        format!("r#{}", name)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Returns the variable of the generated `main` holding the given task
+pub fn get_task_variable(task_name: &str) -> String {
+    get_identifier(&task_name.to_case(Case::Snake))
+}
+
+/// Checks that the name of a task can be turned into the identifiers of the generated code.
+/// This method is invoked by the task function inside the starlark_module.
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds the name of the task
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - Result indicating success if the name is valid, or an error if it
+///   contains other characters than letters, digits, `_` and `-`, does not start with a letter
+///   or clashes with a name of the generated code
+///
+pub fn validate_task_name(name: &str) -> Result<(), Error> {
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(Error::msg(format!(
+            "Invalid task name {:?}, it must start with a letter and contain only letters, \
+            digits, `_` and `-`",
+            name
+        )));
+    }
+
+    let type_name = name.to_case(Case::Pascal);
+    let variable = name.to_case(Case::Snake);
+
+    let clash = [
+        (RESERVED_TYPES, &type_name),
+        (PATH_KEYWORDS, &type_name),
+        (RESERVED_VARIABLES, &variable),
+        (PATH_KEYWORDS, &variable),
+    ]
+    .into_iter()
+    .find(|(reserved, identifier)| reserved.contains(&identifier.as_str()));
+
+    if let Some((_, identifier)) = clash {
+        return Err(Error::msg(format!(
+            "Invalid task name {:?}, it clashes with {} in the generated code",
+            name, identifier
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the name of a workflow is safe to use in the names of its generated package,
+/// build directory and wasm file.
+/// This method is invoked by `Composer::add_workflow`.
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds the name of the workflow
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - Result indicating success if the name starts with a letter and
+///   contains only letters, digits, `_` and `-`, or an error otherwise
+///
+pub fn validate_workflow_name(name: &str) -> Result<(), Error> {
+    if !name.starts_with(|c: char| c.is_ascii_alphabetic())
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(Error::msg(format!(
+            "Invalid workflow name {:?}, it must start with a letter and contain only letters, \
+            digits, `_` and `-`",
+            name
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the name of an argument is a valid field of the generated code.
+/// This method is invoked by the argument function inside the starlark_module.
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds the name of the argument
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - Result indicating success if the name is a Rust identifier, which
+///   is escaped if it is a keyword, or an error otherwise
+///
+pub fn validate_argument_name(name: &str) -> Result<(), Error> {
+    let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name != "_"
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !PATH_KEYWORDS.contains(&name);
+
+    if !valid {
+        return Err(Error::msg(format!(
+            "Invalid argument name {:?}, it must start with a letter or `_` and contain only \
+            letters, digits and `_`{}",
+            name,
+            match PATH_KEYWORDS.contains(&name) {
+                true => ", and must not be one of crate, self, super or Self",
+                false => "",
+            }
+        )));
+    }
+
+    Ok(())
+}
+
+/// Checks that the tasks of a workflow, along with their compensation tasks and the given
+/// user-defined types, do not produce the same identifier once converted to the case of the
+/// generated code, e.g. `get-data` and `get_data` which both become `GetData`. The arguments
+/// of a task must be unique, and so must the arguments not fed by a dependency across the
+/// tasks, as they are all fields of the single input of the workflow.
+/// This method is invoked by the workflows function inside the starlark_module.
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `custom_types` - The names of the user-defined types
+///
+/// # Returns
+///
+/// * `Result<(), Error>` - Result indicating success if every identifier is unique, or an
+///   error naming the two clashing names
+///
+pub fn validate_identifiers<'a>(
+    workflow: &Workflow,
+    custom_types: impl Iterator<Item = &'a String>,
+) -> Result<(), Error> {
+    let workflow = workflow.with_compensations()?;
+    let mut identifiers: HashMap<String, String> = HashMap::new();
+
+    for custom_type in custom_types {
+        identifiers.insert(custom_type.clone(), format!("type {}", custom_type));
+    }
+
+    for (task_name, task) in workflow.sorted_tasks() {
+        let type_name = task_name.to_case(Case::Pascal);
+        let generated = [
+            format!("{}Input", type_name),
+            type_name,
+            task_name.to_case(Case::Snake),
+        ];
+
+        for identifier in generated.iter() {
+            if let Some(other) = identifiers.get(identifier) {
+                return Err(Error::msg(with_span(
+                    format!(
+                        "Task {} clashes with {}, both generate the identifier {}, rename one \
+                        of them",
+                        task_name, other, identifier
+                    ),
+                    task.span.as_ref(),
+                )));
+            }
+        }
+
+        for identifier in generated {
+            identifiers.insert(identifier, format!("task {}", task_name));
+        }
+    }
+
+    let mut fields: HashMap<&String, &String> = HashMap::new();
+
+    for (task_name, task) in workflow.sorted_tasks() {
+        for (index, input) in task.input_arguments.iter().enumerate() {
+            let span = input.span.as_ref().or(task.span.as_ref());
+
+            if task.input_arguments[..index]
+                .iter()
+                .any(|other| other.name == input.name)
+            {
+                return Err(Error::msg(with_span(
+                    format!(
+                        "Argument {} is declared more than once in task {}",
+                        input.name, task_name
+                    ),
+                    span,
+                )));
+            }
+
+            if input.is_depend {
+                continue;
+            }
+
+            if let Some(other) = fields.insert(&input.name, task_name) {
+                return Err(Error::msg(with_span(
+                    format!(
+                        "Argument {} of task {} clashes with the argument of task {}, both are \
+                        fields of the input of the workflow, rename one of them",
+                        input.name, task_name, other
+                    ),
+                    span,
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the candidate the given name was most likely meant to be, if one of them differs
+/// from it only by its case, its separators or up to two characters
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds the name which was not found
+/// * `candidates` - The names which exist
+///
+/// # Returns
+///
+/// * `Option<&String>` - An option containing the closest candidate, or None if none is close
+///
+pub fn get_similar_name<'a>(
+    name: &str,
+    candidates: impl Iterator<Item = &'a String>,
+) -> Option<&'a String> {
+    let snake = name.to_case(Case::Snake);
+
+    candidates
+        .map(|candidate| {
+            let distance = match candidate.to_case(Case::Snake) == snake {
+                true => 0,
+                false => get_edit_distance(name, candidate),
+            };
+
+            (distance, candidate)
+        })
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Returns the number of characters to insert, remove or replace to turn `a` into `b`
+fn get_edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+
+        previous = current;
+    }
+
+    previous[b.len()]
+}
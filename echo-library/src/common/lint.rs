@@ -0,0 +1,347 @@
+use super::*;
+
+/// How the findings of a lint rule are reported
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The rule is not checked
+    Allow,
+    /// The findings are reported without failing the command
+    #[default]
+    Warn,
+    /// The findings are reported as errors
+    Deny,
+}
+
+impl std::str::FromStr for Severity {
+    type Err = Error;
+
+    fn from_str(severity: &str) -> Result<Self, Self::Err> {
+        match severity {
+            "allow" => Ok(Severity::Allow),
+            "warn" => Ok(Severity::Warn),
+            "deny" => Ok(Severity::Deny),
+            _ => Err(Error::msg(format!(
+                "Unknown lint severity {}, expected one of: allow, warn, deny",
+                severity
+            ))),
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Allow => write!(f, "allow"),
+            Severity::Warn => write!(f, "warning"),
+            Severity::Deny => write!(f, "error"),
+        }
+    }
+}
+
+/// Check of the workflows for a mistake which is legal but almost certainly wrong
+pub struct LintRule {
+    pub name: &'static str,
+    pub description: &'static str,
+    check: fn(&Composer) -> Vec<Finding>,
+}
+
+/// Finding of a rule, before its severity and the suppressions are applied
+struct Finding {
+    task: String,
+    message: String,
+    span: Option<Span>,
+}
+
+/// Finding of a lint rule reported to the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub rule: &'static str,
+    pub severity: Severity,
+    /// Task, or action for `duplicate_action`, the finding is about
+    pub task: String,
+    pub message: String,
+    pub span: Option<Span>,
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            with_span(
+                format!("{}[{}]: {}", self.severity, self.rule, self.message),
+                self.span.as_ref()
+            )
+        )
+    }
+}
+
+/// Rule suppressed by `allow_lint()` in a config file, for every task or for one of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Suppression {
+    pub rule: String,
+    pub task: Option<String>,
+}
+
+/// Rules checked by `Composer::lint`, all of them warn by default
+pub const LINT_RULES: &[LintRule] = &[
+    LintRule {
+        name: "unused_task",
+        description: "a task is created but never added to a workflow",
+        check: check_unused_task,
+    },
+    LintRule {
+        name: "unconsumed_output",
+        description: "the output of a task other than the last one of the workflow is not \
+            consumed by any task, so the workflow has more than one sink",
+        check: check_unconsumed_output,
+    },
+    LintRule {
+        name: "default_with_depend",
+        description: "an argument with a default value is also fed by a dependency",
+        check: check_default_with_depend,
+    },
+    LintRule {
+        name: "insecure_attribute",
+        description: "a task disables the verification of the certificate of its API host",
+        check: check_insecure_attribute,
+    },
+    LintRule {
+        name: "duplicate_action",
+        description: "the same action is used by several workflows, other than versions of \
+            the same workflow",
+        check: check_duplicate_action,
+    },
+];
+
+/// Returns the lint rule with the given name
+///
+/// # Arguments
+///
+/// * `name` - A string slice that holds the name of the rule
+///
+/// # Returns
+///
+/// * `Result<&LintRule, Error>` - Result containing the rule, or an error if there is no rule
+///   with this name
+///
+pub fn get_lint_rule(name: &str) -> Result<&'static LintRule, Error> {
+    LINT_RULES
+        .iter()
+        .find(|rule| rule.name == name)
+        .ok_or_else(|| {
+            Error::msg(format!(
+                "Unknown lint rule {}, expected one of: {}",
+                name,
+                LINT_RULES
+                    .iter()
+                    .map(|rule| rule.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ))
+        })
+}
+
+impl Composer {
+    /// Checks the evaluated config files against the lint rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `severities` - A reference to the severities of the rules by rule name, as written
+    ///   in the `[lints]` of the manifest, the other rules warn
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Vec<Lint>, Error>` - Result containing the findings which are not allowed or
+    ///   suppressed by the config files, or an error if a rule or a severity is unknown
+    ///
+    pub fn lint(&self, severities: &BTreeMap<String, String>) -> Result<Vec<Lint>, Error> {
+        let mut levels = HashMap::new();
+
+        for (name, severity) in severities.iter() {
+            let rule = get_lint_rule(name)?;
+            let severity = severity
+                .parse::<Severity>()
+                .map_err(|err| Error::msg(format!("Lint rule {}: {}", name, err)))?;
+            levels.insert(rule.name, severity);
+        }
+
+        let suppressions = self.lint_suppressions.borrow();
+        let mut lints = Vec::new();
+
+        for rule in LINT_RULES {
+            let severity = levels.get(rule.name).copied().unwrap_or_default();
+
+            if severity == Severity::Allow {
+                continue;
+            }
+
+            for finding in (rule.check)(self) {
+                let suppressed = suppressions.iter().any(|suppression| {
+                    suppression.rule == rule.name
+                        && suppression
+                            .task
+                            .as_ref()
+                            .is_none_or(|task| *task == finding.task)
+                });
+
+                if !suppressed {
+                    lints.push(Lint {
+                        rule: rule.name,
+                        severity,
+                        task: finding.task,
+                        message: finding.message,
+                        span: finding.span,
+                    });
+                }
+            }
+        }
+
+        Ok(lints)
+    }
+}
+
+fn check_unused_task(composer: &Composer) -> Vec<Finding> {
+    let workflows: Vec<Workflow> = composer
+        .workflows
+        .borrow()
+        .iter()
+        .filter_map(|workflow| workflow.with_compensations().ok())
+        .collect();
+
+    let mut findings: Vec<Finding> = Vec::new();
+
+    for task in composer.tasks.borrow().iter() {
+        let used = workflows.iter().any(|workflow| {
+            workflow
+                .tasks
+                .values()
+                .any(|used| used.action_name == task.action_name && used.span == task.span)
+        });
+
+        if !used
+            && !findings
+                .iter()
+                .any(|finding| finding.task == task.action_name && finding.span == task.span)
+        {
+            findings.push(Finding {
+                task: task.action_name.clone(),
+                message: format!("Task {} is never added to a workflow", task.action_name),
+                span: task.span.clone(),
+            });
+        }
+    }
+
+    findings
+}
+
+fn check_unconsumed_output(composer: &Composer) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for workflow in composer.workflows.borrow().iter() {
+        let sinks = workflow.get_sinks(&workflow.get_flow());
+
+        for task_name in sinks.iter().take(sinks.len().saturating_sub(1)) {
+            findings.push(Finding {
+                task: task_name.clone(),
+                message: format!(
+                    "The output of task {} is not consumed by any task of workflow {}, the \
+                    workflow returns the outputs of its {} sink tasks keyed by task name",
+                    task_name,
+                    workflow.name,
+                    sinks.len()
+                ),
+                span: workflow.tasks[task_name].span.clone(),
+            });
+        }
+    }
+
+    findings
+}
+
+fn check_default_with_depend(composer: &Composer) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for workflow in composer.workflows.borrow().iter() {
+        let Ok(workflow) = workflow.with_compensations() else {
+            continue;
+        };
+
+        for (task_name, task) in workflow.sorted_tasks() {
+            for input in task.input_arguments.iter() {
+                if input.is_depend && input.default_value.is_some() {
+                    findings.push(Finding {
+                        task: task_name.clone(),
+                        message: format!(
+                            "Argument {} of task {} has a default value but is fed by a \
+                            dependency, the default is never used",
+                            input.name, task_name
+                        ),
+                        span: input.span.clone().or_else(|| task.span.clone()),
+                    });
+                }
+            }
+        }
+    }
+
+    findings
+}
+
+fn check_insecure_attribute(composer: &Composer) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for workflow in composer.workflows.borrow().iter() {
+        let Ok(workflow) = workflow.with_compensations() else {
+            continue;
+        };
+
+        for (task_name, task) in workflow.sorted_tasks() {
+            if task
+                .attributes
+                .get("insecure")
+                .is_some_and(|insecure| insecure.eq_ignore_ascii_case("true"))
+            {
+                findings.push(Finding {
+                    task: task_name.clone(),
+                    message: format!(
+                        "Task {} sets insecure = \"true\", the certificate of its API host is \
+                        not verified",
+                        task_name
+                    ),
+                    span: task.span.clone(),
+                });
+            }
+        }
+    }
+
+    findings
+}
+
+fn check_duplicate_action(composer: &Composer) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let mut actions: HashMap<(String, String), String> = HashMap::new();
+
+    for workflow in composer.workflows.borrow().iter() {
+        for (task_name, task) in workflow.sorted_tasks() {
+            let action = (task.kind.clone(), task.action_name.clone());
+
+            match actions.get(&action) {
+                // Versions of the same workflow built side by side share their actions
+                Some(first) if *first == workflow.name => {}
+                Some(first) => findings.push(Finding {
+                    task: task_name.clone(),
+                    message: format!(
+                        "Action {} of kind {} is used by workflows {} and {}",
+                        task.action_name, task.kind, first, workflow.name
+                    ),
+                    span: task.span.clone(),
+                }),
+                None => {
+                    actions.insert(action, workflow.name.clone());
+                }
+            }
+        }
+    }
+
+    findings
+}
@@ -0,0 +1,186 @@
+use super::*;
+
+/// Name of the file recording the dependencies resolved for the generated packages, next to
+/// the config files of the package
+pub const LOCK_FILE: &str = "composer.lock";
+
+/// Dependencies resolved for the generated packages of all the workflows of a package.
+/// It has the format of a `Cargo.lock` without the generated packages themselves, so that it
+/// can be written as the `Cargo.lock` of any of them, cargo dropping the entries it does not use.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockFile {
+    pub version: u32,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+/// Resolved version of a dependency, along with the git revision in its source for git
+/// dependencies
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<String>,
+}
+
+impl LockFile {
+    /// Reads the lock file at the given path, if there is one
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - A reference to the path of the lock file
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Option<LockFile>, Error>` - Result containing the lock file, None if the file
+    ///   does not exist, or an error if it cannot be read or parsed
+    ///
+    pub fn read(path: &Path) -> Result<Option<LockFile>, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content.parse().map_err(|err| {
+                Error::msg(format!("Invalid lock file {}: {}", path.display(), err))
+            })?)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Adds the packages of the `Cargo.lock` of a generated package, except the generated
+    /// package itself. Packages are identified by their name, version and source, and their
+    /// dependencies are written with the version and the source of the package depended on, so
+    /// that they stay unambiguous once several versions of a crate are locked.
+    ///
+    /// # Arguments
+    ///
+    /// * `cargo_lock` - A reference to the parsed `Cargo.lock`
+    /// * `package_name` - A string slice that holds the name of the generated package
+    ///
+    pub fn merge(&mut self, cargo_lock: &LockFile, package_name: &str) {
+        self.version = self.version.max(cargo_lock.version);
+
+        for package in cargo_lock.packages.iter() {
+            if package.name == package_name {
+                continue;
+            }
+
+            let dependencies = package
+                .dependencies
+                .iter()
+                .map(|dependency| cargo_lock.qualify(dependency));
+
+            match self
+                .packages
+                .iter_mut()
+                .find(|locked| locked.same_package(package))
+            {
+                // The dependencies of a crate depend on the features enabled by each package
+                Some(locked) => {
+                    for dependency in dependencies {
+                        if !locked.dependencies.contains(&dependency) {
+                            locked.dependencies.push(dependency);
+                        }
+                    }
+
+                    locked.dependencies.sort();
+                }
+                None => {
+                    let mut dependencies: Vec<String> = dependencies.collect();
+                    dependencies.sort();
+
+                    self.packages.push(LockedPackage {
+                        dependencies,
+                        ..package.clone()
+                    });
+                }
+            }
+        }
+
+        self.packages.sort();
+    }
+
+    /// Checks whether every package of the `Cargo.lock` of a generated package, except the
+    /// generated package itself, is locked
+    ///
+    /// # Arguments
+    ///
+    /// * `cargo_lock` - A reference to the parsed `Cargo.lock`
+    /// * `package_name` - A string slice that holds the name of the generated package
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if the lock file pins all the dependencies of the generated package
+    ///
+    pub fn pins(&self, cargo_lock: &LockFile, package_name: &str) -> bool {
+        cargo_lock
+            .packages
+            .iter()
+            .filter(|package| package.name != package_name)
+            .all(|package| {
+                self.packages
+                    .iter()
+                    .any(|locked| locked.same_package(package))
+            })
+    }
+
+    /// Returns the given dependency of a package of the lock file along with the version and
+    /// the source of the package it refers to, i.e `name version (source)`. A dependency which
+    /// does not refer to a package of the lock file is returned as it is.
+    fn qualify(&self, dependency: &str) -> String {
+        let (name, rest) = dependency.split_once(' ').unwrap_or((dependency, ""));
+        let (version, source) = match rest.split_once(" (") {
+            Some((version, source)) => (version, source.strip_suffix(')')),
+            None if rest.starts_with('(') => ("", rest[1..].strip_suffix(')')),
+            None => (rest, None),
+        };
+
+        self.packages
+            .iter()
+            .find(|package| {
+                package.name == name
+                    && (version.is_empty() || package.version == version)
+                    && source.is_none_or(|source| package.source.as_deref() == Some(source))
+            })
+            .map(LockedPackage::reference)
+            .unwrap_or_else(|| dependency.to_string())
+    }
+}
+
+impl LockedPackage {
+    /// Returns whether both packages are the same crate, at the same version and from the
+    /// same source
+    fn same_package(&self, other: &LockedPackage) -> bool {
+        self.name == other.name && self.version == other.version && self.source == other.source
+    }
+
+    /// Returns the reference of the package as a dependency in the lock file
+    fn reference(&self) -> String {
+        match &self.source {
+            Some(source) => format!("{} {} ({})", self.name, self.version, source),
+            None => format!("{} {}", self.name, self.version),
+        }
+    }
+}
+
+impl std::str::FromStr for LockFile {
+    type Err = Error;
+
+    fn from_str(content: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(content)?)
+    }
+}
+
+impl Display for LockFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# This file is automatically @generated by composer.")?;
+        writeln!(
+            f,
+            "# It is not intended for manual editing, run `composer update` to refresh it."
+        )?;
+        write!(f, "{}", toml::to_string(self).map_err(|_| fmt::Error)?)
+    }
+}
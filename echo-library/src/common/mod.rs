@@ -1,9 +1,19 @@
 pub use super::*;
 
+pub mod build_options;
 pub mod composer;
+pub mod diagnostics;
+pub mod identifier;
+pub mod lint;
+pub mod lock_file;
 pub mod parse_module;
 pub mod starlark_modules;
 
+pub use build_options::*;
 pub use composer::*;
+pub use diagnostics::*;
+pub use identifier::*;
+pub use lint::*;
+pub use lock_file::*;
 pub use parse_module::*;
 pub use starlark_modules::*;
@@ -12,21 +12,25 @@ use super::*;
 /// * An Err Result with an ErrorKind::NotFound if the input is not valid
 ///
 pub fn get_task_kind(kind: &str) -> Result<String, ErrorKind> {
-    match kind.to_lowercase().as_str() {
-        "openwhisk" => Ok("OpenWhisk".to_string()),
-        "polkadot" => Ok("Polkadot".to_string()),
-        "hello_world" => Ok("HelloWorldDerive".to_string()),
-        _ => Err(ErrorKind::NotFound),
-    }
+    get_kind_schema(kind)
+        .map(|schema| schema.derive.to_string())
+        .ok_or(ErrorKind::NotFound)
+}
+
+/// Returns the crate providing the derive macro of the given kind, which a local copy of the
+/// kind overrides
+pub fn get_kind_crate(kind: &str) -> Option<&'static str> {
+    get_kind_schema(kind).map(|schema| schema.crate_name)
 }
 
 fn get_main_method_code_template(tasks_length: usize) -> String {
     format!(
         "#[allow(dead_code, unused)]
-pub fn main(args: Value) -> Result<Value, String> {{
+pub fn main(args: Value) -> Result<Value, WorkflowError> {{
     const LIMIT: usize = {tasks_length};
     let mut workflow = WorkflowGraph::new(LIMIT);
-    let input: Input = serde_json::from_value(args).map_err(|e| e.to_string())?;
+    let input: Input = from_value_at(args, Phase::Input)?;
+    input.validate()?;
 "
     )
 }
@@ -45,6 +49,8 @@ pub fn main(args: Value) -> Result<Value, String> {{
 /// This formats the value of the attributes as enclosed by double quots
 pub fn get_attributes(attributes: &HashMap<String, String>) -> String {
     let mut build_string = Vec::new();
+    let mut attributes: Vec<(&String, &String)> = attributes.iter().collect();
+    attributes.sort();
 
     for (key, value) in attributes {
         build_string.push(format!("{}:\"{}\"", key.to_case(Case::Pascal), value));
@@ -57,7 +63,7 @@ pub fn get_attributes(attributes: &HashMap<String, String>) -> String {
 fn get_default_value_functions_code(workflow: &Workflow) -> String {
     let mut default_value_functions = String::new();
 
-    for task in workflow.tasks.values() {
+    for (_, task) in workflow.sorted_tasks() {
         for input in task.input_arguments.iter() {
             if !input.is_depend {
                 if let Some(val) = input.default_value.as_ref() {
@@ -100,22 +106,28 @@ fn get_task_common_input_type_constructor(
     workflow: &Workflow,
 ) -> Result<String, Error> {
     let mut common = Vec::<String>::new();
-    let mut workflow_custom_types = Vec::<String>::new();
+    let mut workflow_custom_types = Vec::<(String, Option<&Span>)>::new();
 
-    for task in workflow.tasks.values() {
+    for (_, task) in workflow.sorted_tasks() {
         for input in task.input_arguments.iter() {
             if let RustType::Struct(name) = &input.input_type {
-                workflow_custom_types.push(name.to_string());
+                workflow_custom_types.push((name.to_string(), input.span.as_ref()));
             }
 
             if !input.is_depend {
                 if input.default_value.as_ref().is_some() {
                     common.push(format!(
                         "#[\"{}_fn\"] {}:{}",
-                        input.name, input.name, input.input_type
+                        input.name,
+                        get_identifier(&input.name),
+                        input.input_type
                     ));
                 } else {
-                    common.push(format!("{}:{}", input.name, input.input_type));
+                    common.push(format!(
+                        "{}:{}",
+                        get_identifier(&input.name),
+                        input.input_type
+                    ));
                 };
             }
         }
@@ -124,10 +136,15 @@ fn get_task_common_input_type_constructor(
     let workflow_custom_types = if !workflow_custom_types.is_empty() {
         let mut build_string = String::new();
 
-        for custom_type in workflow_custom_types.iter() {
+        for (custom_type, span) in workflow_custom_types.iter() {
             let typ = match composer_custom_types.get(custom_type) {
                 Some(t) => t,
-                None => return Err(Error::msg("Missing custom type in workflow")),
+                None => {
+                    return Err(Error::msg(with_span(
+                        "Missing custom type in workflow",
+                        *span,
+                    )))
+                }
             };
             build_string = format!("{build_string}{typ}");
         }
@@ -147,21 +164,115 @@ Input,
     ))
 }
 
+/// Creates the Rust code to check the fields of the workflow input against the constraints
+/// declared on the arguments
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+///
+/// # Returns
+///
+/// * A Result containing the Rust code implementing `validate` for the `Input` struct, or
+///   an error if the bound of a min or max constraint is not a number or if an argument fed by
+///   a dependency has constraints, which are only checked on the input of the workflow
+///
+fn get_impl_validate_code(workflow: &Workflow) -> Result<String, Error> {
+    let mut fields = Vec::<String>::new();
+
+    for (task_name, task) in workflow.sorted_tasks() {
+        for input in task.input_arguments.iter() {
+            if input.constraints.is_empty() {
+                continue;
+            }
+
+            if input.is_depend {
+                return Err(Error::msg(format!(
+                    "Task {}: argument {} is fed by a dependency and cannot have constraints",
+                    task_name, input.name
+                )));
+            }
+
+            let constraints = input
+                .constraints
+                .iter()
+                .map(|constraint| {
+                    Ok(match constraint {
+                        Constraint::Min(min) => {
+                            format!("Constraint::Min({:?})", get_bound(&input.name, constraint, min)?)
+                        }
+                        Constraint::Max(max) => {
+                            format!("Constraint::Max({:?})", get_bound(&input.name, constraint, max)?)
+                        }
+                        Constraint::Pattern(pattern) => format!(
+                            "Constraint::Pattern({pattern:?}, |value| regex::Regex::new({pattern:?}).map_or(false, |regex| regex.is_match(value)))"
+                        ),
+                        Constraint::OneOf(values) => format!(
+                            "Constraint::OneOf({:?})",
+                            format!("[{}]", get_allowed_values(&input.input_type, values).join(","))
+                        ),
+                        Constraint::MinLen(min_len) => format!("Constraint::MinLen({min_len})"),
+                        Constraint::NonEmpty => "Constraint::NonEmpty".to_string(),
+                    })
+                })
+                .collect::<Result<Vec<String>, Error>>()?;
+
+            fields.push(format!(
+                "{}:[{}]",
+                get_identifier(&input.name),
+                constraints.join(",")
+            ));
+        }
+    }
+
+    fields.sort();
+
+    Ok(format!("impl_validate!(Input, [{}]);", fields.join(",")))
+}
+
+/// Returns the values allowed by a one_of constraint. The values of a float argument are
+/// rounded to f32, the type of the field, so that they equal the value of the field once it
+/// is serialized
+fn get_allowed_values(input_type: &RustType, values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .map(|value| match (input_type, value.parse::<f64>()) {
+            (RustType::Float, Ok(value)) => {
+                serde_json::Value::from(value as f32 as f64).to_string()
+            }
+            _ => value.clone(),
+        })
+        .collect()
+}
+
+/// Returns the bound of a min or max constraint, or an error if it is not a finite number
+fn get_bound(argument: &str, constraint: &Constraint, bound: &str) -> Result<f64, Error> {
+    match bound.parse::<f64>() {
+        Ok(bound) if bound.is_finite() => Ok(bound),
+        _ => Err(Error::msg(format!(
+            "Argument {}: {} must be a number, got {}",
+            argument,
+            constraint.name(),
+            bound
+        ))),
+    }
+}
+
 fn get_task_type_constructors(workflow: &Workflow) -> String {
     let mut constructors = String::new();
 
-    for task in workflow.tasks.values() {
+    for (_, task) in workflow.sorted_tasks() {
         let mut parameters = String::new();
 
         for argument in task.input_arguments.iter() {
             if !argument.is_depend {
-                parameters.push_str(&format!("input.{},", argument.name));
+                parameters.push_str(&format!("input.{},", get_identifier(&argument.name)));
             }
         }
 
         let constructor = format!(
             "let {} = {}::new({}\"{}\".to_string());\n",
-            task.action_name.to_case(Case::Snake),
+            get_task_variable(&task.action_name),
             task.action_name.to_case(Case::Pascal),
             parameters,
             task.action_name.clone()
@@ -176,11 +287,15 @@ fn get_task_type_constructors(workflow: &Workflow) -> String {
 fn get_task_input_type_constructors(workflow: &Workflow) -> String {
     let mut input_type_build_string = String::new();
 
-    for task in workflow.tasks.values() {
+    for (_, task) in workflow.sorted_tasks() {
         let mut arguments = Vec::new();
 
         for field in task.input_arguments.iter() {
-            arguments.push(format!("{}:{}", field.name, field.input_type));
+            arguments.push(format!(
+                "{}:{}",
+                get_identifier(&field.name),
+                field.input_type
+            ));
         }
 
         input_type_build_string.push_str(&format!(
@@ -198,7 +313,11 @@ fn get_independent_fields(task: &Task) -> Vec<String> {
 
     for field in task.input_arguments.iter() {
         if !field.is_depend {
-            independent_fields.push(format!("{}:{}", field.name, field.input_type));
+            independent_fields.push(format!(
+                "{}:{}",
+                get_identifier(&field.name),
+                field.input_type
+            ));
         }
     }
 
@@ -220,7 +339,7 @@ fn get_independent_fields(task: &Task) -> Vec<String> {
 fn get_task_main_type_constructors(workflow: &Workflow) -> Result<String, Error> {
     let mut input_structs = String::new();
 
-    for (task_name, task) in workflow.tasks.iter() {
+    for (task_name, task) in workflow.sorted_tasks() {
         let task_name = task_name.to_case(Case::Pascal);
 
         let independent_fields = get_independent_fields(task);
@@ -247,7 +366,11 @@ impl_new!(
 );
 ",
             get_task_kind(&task.kind).unwrap(),
-            get_attributes(&task.attributes),
+            get_attributes(
+                &get_kind_schema(&task.kind)
+                    .map(|schema| schema.with_defaults(&task.attributes))
+                    .unwrap_or_else(|| task.attributes.clone())
+            ),
             output_field,
             independent_fields.join(",")
         );
@@ -259,7 +382,7 @@ impl_new!(
 fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
     let mut impl_setters_code = String::new();
 
-    for (task_name, task) in workflow.tasks.iter() {
+    for (task_name, task) in workflow.sorted_tasks() {
         let task_name = task_name.to_case(Case::Pascal);
 
         let mut setter_fields = Vec::<String>::new();
@@ -279,24 +402,34 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
             if task.operation.is_combine() {
                 let dependent_task = match workflow.tasks.get(&dependent.task_name) {
                     Some(t) => t,
-                    None => return Err(Error::msg("Missing custom type in workflow")),
+                    None => {
+                        return Err(Error::msg(with_span(
+                            "Missing custom type in workflow",
+                            dependent.span.as_ref(),
+                        )))
+                    }
                 };
 
                 if dependent_task.operation.is_map() {
                     setter_fields.push(format!(
                         "(value)[{}]{}:\"{}\"",
-                        current_index, dependent.cur_field, dependent.prev_field
+                        current_index,
+                        get_identifier(&dependent.cur_field),
+                        dependent.prev_field
                     ));
                 } else {
                     setter_fields.push(format!(
                         "[{}]{}:\"{}\"",
-                        current_index, dependent.cur_field, dependent.prev_field
+                        current_index,
+                        get_identifier(&dependent.cur_field),
+                        dependent.prev_field
                     ));
                 }
             } else {
                 setter_fields.push(format!(
                     "{}:\"{}\"",
-                    dependent.cur_field, dependent.prev_field
+                    get_identifier(&dependent.cur_field),
+                    dependent.prev_field
                 ));
             }
 
@@ -313,7 +446,8 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
             ),
             Operation::Concat => format!(
                 "impl_concat_setter!({}, {});\n",
-                task_name, task.input_arguments[0].name
+                task_name,
+                get_identifier(&task.input_arguments[0].name)
             ),
             Operation::Combine => format!(
                 "impl_combine_setter!({},[{}]);\n",
@@ -344,49 +478,241 @@ fn get_impl_setters_code(workflow: &Workflow) -> Result<String, Error> {
 ///
 /// * A String containing the Rust code to call the `impl_execute_trait!` macro
 ///
+/// Generates Rust code to call the `impl_invoke!` macro, which runs a task once. An OpenWhisk
+/// action is invoked with the timeout of its task, or else of the workflow, so that a hung
+/// action does not block the workflow. The tasks of the other kinds cannot be bounded while
+/// they run, their timeouts are only checked once they return.
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+///
+/// # Returns
+///
+/// * A String containing the Rust code to call the `impl_invoke!` macro for every task
+///
+fn get_impl_invoke_code(workflow: &Workflow) -> String {
+    let mut invoke_code = String::new();
+
+    for (task_name, task) in workflow.sorted_tasks() {
+        let task_name = task_name.to_case(Case::Pascal);
+        let timeout_ms = task.timeout_ms.into_iter().chain(workflow.timeout_ms).min();
+
+        match timeout_ms {
+            Some(timeout_ms) if task.kind == "openwhisk" => {
+                invoke_code.push_str(&format!("impl_invoke!({task_name}, {timeout_ms});\n"))
+            }
+            _ => invoke_code.push_str(&format!("impl_invoke!({task_name});\n")),
+        }
+    }
+
+    invoke_code
+}
+
 fn get_impl_execute_trait_code(workflow: &Workflow) -> String {
     let mut task_names = Vec::new();
+    let mut repeat_code = String::new();
+
+    for (task_name, task) in workflow.sorted_tasks() {
+        match &task.operation {
+            Operation::RepeatUntil(repeat) => {
+                repeat_code.push_str(&get_impl_repeat_until_code(task_name, repeat))
+            }
+            _ => task_names.push(task_name.to_case(Case::Pascal)),
+        }
+    }
 
-    for task_name in workflow.tasks.keys() {
-        task_names.push(task_name.to_case(Case::Pascal));
+    if task_names.is_empty() {
+        return repeat_code;
     }
 
-    format!("impl_execute_trait!({});", task_names.join(","))
+    format!(
+        "impl_execute_trait!({});{repeat_code}",
+        task_names.join(",")
+    )
+}
+
+/// Generates Rust code to call the `impl_repeat_until!` macro, which implements the execute
+/// trait for a task that is executed until its output satisfies the loop condition
+///
+/// # Arguments
+///
+/// * `task_name` - The name of the task
+/// * `repeat` - A reference to the loop definition of the task
+///
+/// # Returns
+///
+/// * A String containing the Rust code to call the `impl_repeat_until!` macro
+///
+fn get_impl_repeat_until_code(task_name: &str, repeat: &Repeat) -> String {
+    let mut feedback = repeat
+        .feedback
+        .iter()
+        .map(|(cur_field, prev_field)| {
+            format!("{}:\"{}\"", get_identifier(cur_field), prev_field)
+        })
+        .collect::<Vec<String>>();
+    feedback.sort();
+
+    format!(
+        "\nimpl_repeat_until!({}, \"{}\", {:?}, {}, [{}]);",
+        task_name.to_case(Case::Pascal),
+        repeat.field,
+        repeat.value,
+        repeat.max_iterations,
+        feedback.join(",")
+    )
 }
 
-fn get_add_nodes_code(flow: &Vec<String>) -> String {
+fn get_add_nodes_code(flow: &[String]) -> String {
     let mut add_nodes_code = String::new();
 
     for i in flow {
         add_nodes_code.push_str(&format!(
             "let {}_index = workflow.add_node(Box::new({}));\n",
             i.to_case(Case::Snake),
-            i.to_case(Case::Snake)
+            get_task_variable(i)
         ));
     }
 
     add_nodes_code
 }
 
-fn get_add_edges_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String, Error> {
+/// Generates Rust code to register the compensation tasks of the workflow tasks
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `flow` - A slice of task names in the order of the topological sort
+///
+/// # Returns
+///
+/// * A String containing the Rust code to register the compensation tasks
+///
+fn get_add_compensations_code(workflow: &Workflow, flow: &[String]) -> String {
+    let mut add_compensations_code = String::new();
+
+    for task_name in flow {
+        if let Some(compensation) = workflow
+            .tasks
+            .get(task_name)
+            .and_then(|task| task.compensate.as_ref())
+        {
+            add_compensations_code.push_str(&format!(
+                "workflow.add_compensation({}_index, Box::new({}.clone()));\n",
+                task_name.to_case(Case::Snake),
+                get_task_variable(&compensation.action_name)
+            ));
+        }
+    }
+
+    add_compensations_code
+}
+
+/// Generates Rust code to set the timeouts of the workflow and of its tasks
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `flow` - A slice of task names in the order of the topological sort
+///
+/// # Returns
+///
+/// * A String containing the Rust code to set the timeouts
+///
+fn get_add_timeouts_code(workflow: &Workflow, flow: &[String]) -> String {
+    let mut add_timeouts_code = match workflow.timeout_ms {
+        Some(timeout_ms) => format!("workflow.set_timeout({timeout_ms});\n"),
+        None => String::new(),
+    };
+
+    for task_name in flow {
+        if let Some(timeout_ms) = workflow
+            .tasks
+            .get(task_name)
+            .and_then(|task| task.timeout_ms)
+        {
+            add_timeouts_code.push_str(&format!(
+                "workflow.set_task_timeout({}_index, {});\n",
+                task_name.to_case(Case::Snake),
+                timeout_ms
+            ));
+        }
+    }
+
+    add_timeouts_code
+}
+
+/// Generates Rust code to set the retries of the tasks
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `flow` - A slice of task names in the order of the topological sort
+///
+/// # Returns
+///
+/// * A String containing the Rust code to set the retries
+///
+fn get_add_retries_code(workflow: &Workflow, flow: &[String]) -> String {
+    let mut add_retries_code = String::new();
+
+    for task_name in flow {
+        if let Some(retry) = workflow.tasks.get(task_name).and_then(|task| task.retry) {
+            add_retries_code.push_str(&format!(
+                "workflow.set_task_retries({}_index, {});\n",
+                task_name.to_case(Case::Snake),
+                retry
+            ));
+        }
+    }
+
+    add_retries_code
+}
+
+/// Generates Rust code to add an edge from every task to each of the tasks depending on it.
+/// Edges of a task are added in the order of its `depend_on` list, which is the order in
+/// which the outputs are passed to the setter of the task.
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `flow` - A slice of task names in the order of the topological sort
+///
+/// # Returns
+///
+/// * A Result containing the Rust code to add the workflow edges, or an error if a task
+///   depends on a task that is not part of the workflow
+///
+fn get_add_edges_code(workflow: &Workflow, flow: &[String]) -> Result<String, Error> {
     let mut add_edges_code = "workflow.add_edges(&[\n".to_string();
 
-    for index in 0..flow.len() - 1 {
-        if let Some(dependent_task) = workflow.tasks.get(&flow[index + 1]) {
-            let mut set = HashSet::<String>::new();
+    for task_name in flow {
+        let task = match workflow.tasks.get(task_name) {
+            Some(task) => task,
+            None => return Err(Error::msg(" Error adding the edges ")),
+        };
 
-            for dependent_task in dependent_task.depend_on.iter() {
-                if !set.contains(&dependent_task.task_name) {
-                    add_edges_code = format!(
-                        "{add_edges_code}({}_index, {}_index),\n",
-                        dependent_task.task_name.to_case(Case::Snake),
-                        flow[index + 1].to_case(Case::Snake)
-                    );
-                    set.insert(dependent_task.task_name.clone());
-                }
+        let mut set = HashSet::<String>::new();
+
+        for dependent_task in task.depend_on.iter() {
+            if !workflow.tasks.contains_key(&dependent_task.task_name) {
+                return Err(Error::msg(with_span(
+                    format!(
+                        "Task {} depends on {}, which is not part of the workflow",
+                        task_name, dependent_task.task_name
+                    ),
+                    dependent_task.span.as_ref().or(task.span.as_ref()),
+                )));
+            }
+
+            if set.insert(dependent_task.task_name.clone()) {
+                add_edges_code = format!(
+                    "{add_edges_code}({}_index, {}_index),\n",
+                    dependent_task.task_name.to_case(Case::Snake),
+                    task_name.to_case(Case::Snake)
+                );
             }
-        } else {
-            return Err(Error::msg(" Error adding the edges "));
         }
     }
 
@@ -394,41 +720,35 @@ fn get_add_edges_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String,
     Ok(add_edges_code)
 }
 
-fn get_add_execute_workflow_code(workflow: &Workflow, flow: &Vec<String>) -> Result<String, Error> {
-    let mut execute_code = "let result = workflow\n.init()?".to_string();
-
-    for task_index in 0..flow.len() - 1 {
-        execute_code = if task_index + 1 == flow.len() - 1 {
-            match workflow
-                .tasks
-                .get(&flow[task_index + 1])
-                .unwrap()
-                .depend_on
-                .len()
-            {
-                0 | 1 => {
-                    format!(
-                        "{execute_code}\n.term(Some({}_index))?;",
-                        flow[task_index + 1].to_case(Case::Snake)
-                    )
-                }
+/// Generates Rust code to execute the tasks in the order of the topological sort and to
+/// collect the outputs of the sink tasks as the result of the workflow
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `flow` - A slice of task names in the order of the topological sort
+///
+/// # Returns
+///
+/// * A Result containing the Rust code to execute the workflow
+///
+fn get_add_execute_workflow_code(workflow: &Workflow, flow: &[String]) -> Result<String, Error> {
+    let mut execute_code = "let result = workflow".to_string();
 
-                _ => {
-                    format!(
-                        "{execute_code}\n.pipe({}_index)?\n.term(None)?;",
-                        flow[task_index + 1].to_case(Case::Snake)
-                    )
-                }
-            }
-        } else {
-            format!(
-                "{execute_code}\n.pipe({}_index)?",
-                flow[task_index + 1].to_case(Case::Snake)
-            )
-        }
+    for task_name in flow {
+        execute_code = format!(
+            "{execute_code}\n.dispatch({}_index)?",
+            task_name.to_case(Case::Snake)
+        );
     }
 
-    Ok(execute_code)
+    let sinks = workflow
+        .get_sinks(flow)
+        .iter()
+        .map(|task_name| format!("(\"{}\", {}_index)", task_name, task_name.to_case(Case::Snake)))
+        .collect::<Vec<String>>();
+
+    Ok(format!("{execute_code}\n.outputs(&[{}]);", sinks.join(", ")))
 }
 
 /// Generates Rust code to add workflow nodes and edges
@@ -448,22 +768,12 @@ fn get_workflow_nodes_and_edges_code(workflow: &Workflow) -> Result<String, Erro
         return Ok("".to_string());
     }
 
-    if flow.len() == 1 {
-        return Ok(format!(
-            "\
-let {}_index = workflow.add_node(Box::new({}));
-\tlet result = workflow\n\t\t.init()?
-\t\t.term(None)?;
-Ok(result)
-",
-            flow[0].to_case(Case::Snake),
-            flow[0].to_case(Case::Snake)
-        ));
-    }
-
     Ok(format!(
-        "{}\n{}\n{}let result = serde_json::to_value(result).unwrap();\nOk(result)",
+        "{}{}{}{}\n{}\n{}\nOk(result)",
         get_add_nodes_code(&flow),
+        get_add_compensations_code(workflow, &flow),
+        get_add_timeouts_code(workflow, &flow),
+        get_add_retries_code(workflow, &flow),
         get_add_edges_code(workflow, &flow)?,
         get_add_execute_workflow_code(workflow, &flow)?,
     ))
@@ -483,17 +793,21 @@ pub fn generate_types_rs_file_code(
     workflow: &Workflow,
     custom_types: &HashMap<String, String>,
 ) -> Result<String, Error> {
+    let all_tasks = workflow.with_compensations()?;
+
     let main_file = format!(
-        "use super::*;\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}}}",
+        "use super::*;\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}}}",
         add_polkadot_openwhisk(workflow),
-        get_task_input_type_constructors(workflow),
-        get_task_main_type_constructors(workflow)?,
-        get_impl_setters_code(workflow)?,
-        get_default_value_functions_code(workflow),
-        get_task_common_input_type_constructor(custom_types, workflow)?,
-        get_impl_execute_trait_code(workflow),
+        get_task_input_type_constructors(&all_tasks),
+        get_task_main_type_constructors(&all_tasks)?,
+        get_impl_setters_code(&all_tasks)?,
+        get_default_value_functions_code(&all_tasks),
+        get_task_common_input_type_constructor(custom_types, &all_tasks)?,
+        get_impl_validate_code(&all_tasks)?,
+        get_impl_invoke_code(&all_tasks),
+        get_impl_execute_trait_code(&all_tasks),
         get_main_method_code_template(workflow.tasks.len()),
-        get_task_type_constructors(workflow),
+        get_task_type_constructors(&all_tasks),
         get_workflow_nodes_and_edges_code(workflow)?
     );
     Ok(main_file)
@@ -518,8 +832,58 @@ sp-runtime = { version = \"6.0.0\", default-features = false, git = \"https://gi
         .to_string()
 }
 
-pub fn generate_cargo_toml_dependencies(workflow: &Workflow) -> String {
-    let mut dependency_map = HashMap::new();
+/// Git repository the dependencies of a kind are fetched from
+struct GitSource {
+    kind: &'static str,
+    url: &'static str,
+    /// Key and value of the git reference used (i.e `branch`, `rev`)
+    reference: (&'static str, &'static str),
+    /// Crates depended on directly from the repository
+    crates: &'static [&'static str],
+}
+
+const KIND_GIT_SOURCES: &[GitSource] = &[
+    GitSource {
+        kind: "hello_world",
+        url: "https://github.com/HugoByte/aurras.git",
+        reference: ("branch", "next"),
+        crates: &["hello_world_macro"],
+    },
+    GitSource {
+        kind: "polkadot",
+        url: "https://github.com/paritytech/substrate.git",
+        reference: ("rev", "eb1a2a8"),
+        crates: &["pallet-staking", "sp-core", "sp-runtime"],
+    },
+    GitSource {
+        kind: "polkadot",
+        url: "https://github.com/HugoByte/substrate-api-client.git",
+        reference: ("branch", "wasm-support"),
+        crates: &["substrate-api-client"],
+    },
+];
+
+/// Generates the dependencies of the kinds used by the workflow to be appended to the
+/// `Cargo.toml` of the workflow package, followed by the `[patch]` sections overriding
+/// dependencies with local copies. The `regex` crate is only added for the workflows whose
+/// arguments have a pattern constraint
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `patches` - A reference to a map of crate names to the local path overriding the crate.
+///   A crate fetched from git by a kind is patched in its git repository, any other crate is
+///   patched in crates.io
+///
+/// # Returns
+///
+/// * A String containing the dependencies and the patches in TOML
+///
+pub fn generate_cargo_toml_dependencies(
+    workflow: &Workflow,
+    patches: &BTreeMap<String, PathBuf>,
+) -> String {
+    let mut dependency_map = BTreeMap::new();
 
     let hello_world_dependency = "hello_world_macro = {git= \"https://github.com/HugoByte/aurras.git\", branch = \"next\", package = \"hello_world_macro\"}"
     .to_string();
@@ -529,20 +893,97 @@ pub fn generate_cargo_toml_dependencies(workflow: &Workflow) -> String {
     dependency_map.insert("polkadot", get_polkadot_kind_dependencies());
 
     let kinds = get_common_kind(workflow);
-    if kinds.is_empty() {
+    let uses_pattern = workflow
+        .tasks
+        .values()
+        .flat_map(|task| std::iter::once(task).chain(task.compensate.as_deref()))
+        .flat_map(|task| task.input_arguments.iter())
+        .filter(|input| !input.is_depend)
+        .flat_map(|input| input.constraints.iter())
+        .any(|constraint| matches!(constraint, Constraint::Pattern(_)));
+
+    if kinds.is_empty() && patches.is_empty() && !uses_pattern {
         return String::new();
     }
 
     let mut toml_dependencies = String::new();
     for (kind, dependency_string) in dependency_map.iter() {
-        if kinds.contains(&kind.to_string()) {
+        if kinds.contains(*kind) {
             toml_dependencies.push_str(dependency_string);
         }
     }
 
+    if uses_pattern {
+        toml_dependencies.push_str("regex = \"1\"\n");
+    }
+
+    let mut patch_sections: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+
+    for (name, path) in patches {
+        let source = KIND_GIT_SOURCES
+            .iter()
+            .find(|source| source.crates.contains(&name.as_str()))
+            .map_or("crates-io", |source| source.url);
+
+        patch_sections
+            .entry(source)
+            .or_default()
+            .push(format!("{} = {{ path = {:?} }}", name, path.display().to_string()));
+    }
+
+    for (source, patches) in patch_sections {
+        let section = if source == "crates-io" {
+            source.to_string()
+        } else {
+            format!("{source:?}")
+        };
+
+        toml_dependencies.push_str(&format!("\n[patch.{}]\n{}\n", section, patches.join("\n")));
+    }
+
     toml_dependencies
 }
 
+/// Generates the cargo configuration of the workflow package replacing crates.io and the git
+/// repositories of the kinds used by the workflow with a vendor directory, such as the one
+/// created by `cargo vendor`
+///
+/// # Arguments
+///
+/// * `workflow` - A reference to the workflow
+/// * `vendor_dir` - A reference to the vendor directory
+///
+/// # Returns
+///
+/// * A String containing the `[source]` sections of the `.cargo/config.toml` in TOML
+///
+pub fn generate_cargo_config(workflow: &Workflow, vendor_dir: &Path) -> String {
+    let kinds = get_common_kind(workflow);
+
+    let mut config = "[source.crates-io]\nreplace-with = \"vendored-sources\"\n".to_string();
+
+    for GitSource {
+        kind,
+        url,
+        reference: (reference, value),
+        ..
+    } in KIND_GIT_SOURCES
+    {
+        if kinds.contains(*kind) {
+            config.push_str(&format!(
+                "\n[source.\"git+{url}?{reference}={value}\"]\ngit = \"{url}\"\n{reference} = \"{value}\"\nreplace-with = \"vendored-sources\"\n"
+            ));
+        }
+    }
+
+    config.push_str(&format!(
+        "\n[source.vendored-sources]\ndirectory = {:?}\n",
+        vendor_dir.display().to_string()
+    ));
+
+    config
+}
+
 pub fn handle_multiple_dependency() -> String {
     let openwhisk_dependency = get_openwhisk_kind_dependencies();
     let polkadot_dependency = get_polkadot_kind_dependencies();
@@ -625,8 +1066,12 @@ pub fn get_struct_stake_ledger(workflow: &Workflow) -> String {
 
 pub fn get_common_kind(workflow: &Workflow) -> HashSet<String> {
     let mut kinds = HashSet::new();
-    for task in workflow.tasks.values() {
+    for (_, task) in workflow.sorted_tasks() {
         kinds.insert(task.kind.to_lowercase());
+
+        if let Some(compensation) = &task.compensate {
+            kinds.insert(compensation.kind.to_lowercase());
+        }
     }
     kinds
 }
@@ -650,10 +1095,11 @@ mod tests {
         assert_eq!(
             &output,
             "#[allow(dead_code, unused)]
-pub fn main(args: Value) -> Result<Value, String> {
+pub fn main(args: Value) -> Result<Value, WorkflowError> {
     const LIMIT: usize = 4;
     let mut workflow = WorkflowGraph::new(LIMIT);
-    let input: Input = serde_json::from_value(args).map_err(|e| e.to_string())?;
+    let input: Input = from_value_at(args, Phase::Input)?;
+    input.validate()?;
 "
         );
     }
@@ -665,7 +1111,12 @@ pub fn main(args: Value) -> Result<Value, String> {
 
         let output = get_attributes(&attributes);
         assert_eq!(output, "[Key:\"value\"]");
-      
+
+        attributes.insert("namespace".to_string(), "guest".to_string());
+        attributes.insert("api_host".to_string(), "host".to_string());
+
+        let output = get_attributes(&attributes);
+        assert_eq!(output, "[ApiHost:\"host\",Key:\"value\",Namespace:\"guest\"]");
     }
 
     #[test]
@@ -696,6 +1147,7 @@ pub fn main(args: Value) -> Result<Value, String> {
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_default_value_functions_code(&workflow);
@@ -770,6 +1222,7 @@ pub fn argument_2_fn() -> Vec<String>{let val = serde_json::from_str::<Vec<Strin
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let mut custom_types = HashMap::new();
@@ -820,6 +1273,7 @@ Input,
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_task_type_constructors(&workflow);
@@ -856,6 +1310,7 @@ Input,
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_task_input_type_constructors(&workflow);
@@ -924,6 +1379,7 @@ Task0Input,
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_task_main_type_constructors(&workflow);
@@ -935,7 +1391,7 @@ make_main_struct!(
     Task0,
     Task0Input,
     [Debug, Clone, Default, Serialize, Deserialize, OpenWhisk],
-    [],
+    [AuthKey:\"\",Insecure:\"false\"],
     output
 );
 impl_new!(
@@ -969,6 +1425,7 @@ impl_new!(
                 task_name: "task1".to_string(),
                 cur_field: "argument_1".to_string(),
                 prev_field: "data_field".to_string(),
+                ..Default::default()
             }],
             ..Default::default()
         };
@@ -980,6 +1437,7 @@ impl_new!(
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_impl_setters_code(&workflow);
@@ -1012,6 +1470,7 @@ impl_new!(
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let output = get_impl_execute_trait_code(&workflow);
@@ -1022,70 +1481,198 @@ impl_new!(
     }
 
     #[test]
-    fn test_get_add_nodes_code() {
-        let flow = vec![
-            "task0".to_string(),
-            "task2".to_string(),
-            "task1".to_string(),
-            "task4".to_string(),
-            "task3".to_string(),
-        ];
-
-        let output = get_add_nodes_code(&flow);
-
-        assert_eq!(
-            output,
-            "\
-let task_0_index = workflow.add_node(Box::new(task_0));
-let task_2_index = workflow.add_node(Box::new(task_2));
-let task_1_index = workflow.add_node(Box::new(task_1));
-let task_4_index = workflow.add_node(Box::new(task_4));
-let task_3_index = workflow.add_node(Box::new(task_3));
-"
-        )
-    }
-
-    #[test]
-    fn test_get_add_edges_code() {
+    fn test_get_impl_execute_trait_code_repeat_until() {
         let task0 = Task {
             action_name: "task0".to_string(),
+            kind: "Openwhisk".to_string(),
             ..Default::default()
         };
+
+        let mut feedback = HashMap::new();
+        feedback.insert("page".to_string(), "next_page".to_string());
+
         let task1 = Task {
             action_name: "task1".to_string(),
-            depend_on: vec![Depend {
-                task_name: "task0".to_string(),
-                ..Default::default()
-            }],
+            kind: "Openwhisk".to_string(),
+            operation: Operation::RepeatUntil(Repeat {
+                field: "claimable".to_string(),
+                value: "true".to_string(),
+                max_iterations: 10,
+                feedback,
+            }),
             ..Default::default()
         };
 
-        let task2 = Task {
-            action_name: "task2".to_string(),
-            depend_on: vec![
-                Depend {
-                    task_name: "task1".to_string(),
-                    ..Default::default()
-                },
-                Depend {
-                    task_name: "task0".to_string(),
-                    ..Default::default()
-                },
-            ],
-            ..Default::default()
-        };
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
 
-        let task3 = Task {
-            action_name: "task3".to_string(),
-            depend_on: vec![Depend {
-                task_name: "task2".to_string(),
-                ..Default::default()
-            }],
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
             ..Default::default()
         };
 
-        let task4 = Task {
-            action_name: "task4".to_string(),
+        let output = get_impl_execute_trait_code(&workflow);
+        assert_eq!(
+            output,
+            "impl_execute_trait!(Task0);
+impl_repeat_until!(Task1, \"claimable\", \"true\", 10, [page:\"next_page\"]);"
+        );
+    }
+
+    #[test]
+    fn test_get_impl_validate_code() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            kind: "Openwhisk".to_string(),
+            input_arguments: vec![
+                Input {
+                    name: "amount".to_string(),
+                    input_type: RustType::Int,
+                    constraints: vec![
+                        Constraint::Min("1".to_string()),
+                        Constraint::Max("100.5".to_string()),
+                    ],
+                    ..Default::default()
+                },
+                Input {
+                    name: "address".to_string(),
+                    input_type: RustType::String,
+                    constraints: vec![
+                        Constraint::Pattern("^0x[0-9a-f]+$".to_string()),
+                        Constraint::OneOf(vec!["\"0x1\"".to_string(), "\"0x2\"".to_string()]),
+                    ],
+                    ..Default::default()
+                },
+                Input {
+                    name: "rate".to_string(),
+                    input_type: RustType::Float,
+                    constraints: vec![Constraint::OneOf(vec![
+                        "0.1".to_string(),
+                        "1".to_string(),
+                    ])],
+                    ..Default::default()
+                },
+                Input {
+                    name: "tags".to_string(),
+                    input_type: RustType::List(Box::new(RustType::String)),
+                    constraints: vec![Constraint::MinLen(2), Constraint::NonEmpty],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_impl_validate_code(&workflow).unwrap(),
+            "impl_validate!(Input, [\
+address:[Constraint::Pattern(\"^0x[0-9a-f]+$\", |value| regex::Regex::new(\"^0x[0-9a-f]+$\")\
+.map_or(false, |regex| regex.is_match(value))),Constraint::OneOf(\"[\\\"0x1\\\",\\\"0x2\\\"]\")],\
+amount:[Constraint::Min(1.0),Constraint::Max(100.5)],\
+rate:[Constraint::OneOf(\"[0.10000000149011612,1.0]\")],\
+tags:[Constraint::MinLen(2),Constraint::NonEmpty]]);"
+        );
+        assert!(generate_cargo_toml_dependencies(&workflow, &BTreeMap::new())
+            .contains("regex = \"1\"\n"));
+
+        let mut workflow = workflow;
+        workflow.tasks.get_mut("task0").unwrap().input_arguments[0].constraints =
+            vec![Constraint::Min("one".to_string())];
+        assert_eq!(
+            get_impl_validate_code(&workflow).unwrap_err().to_string(),
+            "Argument amount: min must be a number, got one"
+        );
+
+        workflow.tasks.get_mut("task0").unwrap().input_arguments[0].is_depend = true;
+        assert_eq!(
+            get_impl_validate_code(&workflow).unwrap_err().to_string(),
+            "Task task0: argument amount is fed by a dependency and cannot have constraints"
+        );
+
+        let workflow = Workflow::default();
+        assert_eq!(
+            get_impl_validate_code(&workflow).unwrap(),
+            "impl_validate!(Input, []);"
+        );
+    }
+
+    #[test]
+    fn test_get_add_nodes_code() {
+        let flow = vec![
+            "task0".to_string(),
+            "task2".to_string(),
+            "task1".to_string(),
+            "task4".to_string(),
+            "task3".to_string(),
+        ];
+
+        let output = get_add_nodes_code(&flow);
+
+        assert_eq!(
+            output,
+            "\
+let task_0_index = workflow.add_node(Box::new(task_0));
+let task_2_index = workflow.add_node(Box::new(task_2));
+let task_1_index = workflow.add_node(Box::new(task_1));
+let task_4_index = workflow.add_node(Box::new(task_4));
+let task_3_index = workflow.add_node(Box::new(task_3));
+"
+        )
+    }
+
+    #[test]
+    fn test_get_add_edges_code() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            ..Default::default()
+        };
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let task2 = Task {
+            action_name: "task2".to_string(),
+            depend_on: vec![
+                Depend {
+                    task_name: "task1".to_string(),
+                    ..Default::default()
+                },
+                Depend {
+                    task_name: "task0".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let task3 = Task {
+            action_name: "task3".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task2".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let task4 = Task {
+            action_name: "task4".to_string(),
             depend_on: vec![
                 Depend {
                     task_name: "task3".to_string(),
@@ -1110,6 +1697,7 @@ let task_3_index = workflow.add_node(Box::new(task_3));
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let flow = workflow.get_flow();
@@ -1195,6 +1783,7 @@ workflow.add_edges(&[
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks,
+            ..Default::default()
         };
 
         let flow = workflow.get_flow();
@@ -1205,12 +1794,439 @@ workflow.add_edges(&[
             output.unwrap(),
             "\
 let result = workflow
-.init()?
-.pipe(task_1_index)?
-.pipe(task_2_index)?
-.pipe(task_3_index)?
-.pipe(task_4_index)?
-.term(None)?;"
+.dispatch(task_0_index)?
+.dispatch(task_1_index)?
+.dispatch(task_2_index)?
+.dispatch(task_3_index)?
+.dispatch(task_4_index)?
+.outputs(&[(\"task4\", task_4_index)]);"
+        );
+    }
+
+    #[test]
+    fn test_get_add_edges_and_execute_code_diamond() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            ..Default::default()
+        };
+
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let task2 = Task {
+            action_name: "task2".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let task3 = Task {
+            action_name: "task3".to_string(),
+            depend_on: vec![
+                Depend {
+                    task_name: "task2".to_string(),
+                    ..Default::default()
+                },
+                Depend {
+                    task_name: "task1".to_string(),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let task4 = Task {
+            action_name: "task4".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task1".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
+        tasks.insert("task2".to_string(), task2);
+        tasks.insert("task3".to_string(), task3);
+        tasks.insert("task4".to_string(), task4);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let flow = vec![
+            "task0".to_string(),
+            "task1".to_string(),
+            "task2".to_string(),
+            "task3".to_string(),
+            "task4".to_string(),
+        ];
+
+        let output = get_add_edges_code(&workflow, &flow);
+
+        assert_eq!(
+            output.unwrap(),
+            "\
+workflow.add_edges(&[
+(task_0_index, task_1_index),
+(task_0_index, task_2_index),
+(task_2_index, task_3_index),
+(task_1_index, task_3_index),
+(task_1_index, task_4_index),
+]);"
+        );
+
+        let output = get_add_execute_workflow_code(&workflow, &flow);
+
+        assert_eq!(
+            output.unwrap(),
+            "\
+let result = workflow
+.dispatch(task_0_index)?
+.dispatch(task_1_index)?
+.dispatch(task_2_index)?
+.dispatch(task_3_index)?
+.dispatch(task_4_index)?
+.outputs(&[(\"task3\", task_3_index), (\"task4\", task_4_index)]);"
+        );
+    }
+
+    #[test]
+    fn test_get_add_compensations_code() {
+        let refund = Task {
+            action_name: "refund".to_string(),
+            depend_on: vec![Depend {
+                task_name: "transfer".to_string(),
+                cur_field: "tx_hash".to_string(),
+                prev_field: "tx_hash".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let transfer = Task {
+            action_name: "transfer".to_string(),
+            compensate: Some(Box::new(refund)),
+            ..Default::default()
+        };
+
+        let notify = Task {
+            action_name: "notify".to_string(),
+            depend_on: vec![Depend {
+                task_name: "transfer".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("transfer".to_string(), transfer);
+        tasks.insert("notify".to_string(), notify);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let flow = workflow.get_flow();
+        let output = get_add_compensations_code(&workflow, &flow);
+
+        assert_eq!(
+            output,
+            "workflow.add_compensation(transfer_index, Box::new(refund.clone()));\n"
+        );
+
+        let all_tasks = workflow.with_compensations().unwrap();
+
+        assert_eq!(all_tasks.tasks.len(), 3);
+        assert!(all_tasks.get_flow().contains(&"refund".to_string()));
+        assert!(!flow.contains(&"refund".to_string()));
+    }
+
+    #[test]
+    fn test_generate_compensation_code() {
+        let refund = Task {
+            action_name: "refund".to_string(),
+            kind: "openwhisk".to_string(),
+            input_arguments: vec![Input {
+                name: "tx_hash".to_string(),
+                input_type: RustType::String,
+                is_depend: true,
+                ..Default::default()
+            }],
+            depend_on: vec![Depend {
+                task_name: "transfer".to_string(),
+                cur_field: "tx_hash".to_string(),
+                prev_field: "hash".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let transfer = Task {
+            action_name: "transfer".to_string(),
+            kind: "openwhisk".to_string(),
+            compensate: Some(Box::new(refund)),
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("transfer".to_string(), transfer);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let types_rs = generate_types_rs_file_code(&workflow, &HashMap::new()).unwrap();
+
+        // The compensation task gets its types and its setter, but is not a node of the graph
+        assert!(types_rs.contains("make_input_struct!(\nRefundInput,\n[tx_hash:String],"));
+        assert!(types_rs.contains("make_main_struct!(\n    Refund,"));
+        assert!(types_rs.contains("impl_setter!(Refund, [tx_hash:\"hash\"]);"));
+        assert!(types_rs.contains("impl_execute_trait!(Refund,Transfer);"));
+        assert!(types_rs.contains("let refund = Refund::new(\"refund\".to_string());"));
+        assert!(types_rs
+            .contains("workflow.add_compensation(transfer_index, Box::new(refund.clone()));"));
+        assert!(types_rs.contains("let transfer_index = workflow.add_node(Box::new(transfer));"));
+        assert!(!types_rs.contains("add_node(Box::new(refund))"));
+    }
+
+    #[test]
+    fn test_get_add_timeouts_code() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            timeout_ms: Some(5000),
+            ..Default::default()
+        };
+
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            timeout_ms: Some(30000),
+        };
+
+        let flow = vec!["task0".to_string(), "task1".to_string()];
+        let output = get_add_timeouts_code(&workflow, &flow);
+
+        assert_eq!(
+            output,
+            "\
+workflow.set_timeout(30000);
+workflow.set_task_timeout(task_0_index, 5000);
+"
+        );
+    }
+
+    #[test]
+    fn test_get_add_retries_code() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            retry: Some(3),
+            ..Default::default()
+        };
+
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            timeout_ms: None,
+        };
+
+        let flow = vec!["task0".to_string(), "task1".to_string()];
+        let output = get_add_retries_code(&workflow, &flow);
+
+        assert_eq!(output, "workflow.set_task_retries(task_0_index, 3);\n");
+    }
+
+    #[test]
+    fn test_get_add_edges_code_fail() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task1".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let output = get_add_edges_code(&workflow, &["task0".to_string()]);
+
+        assert!(output.is_err());
+    }
+
+    #[test]
+    fn test_generate_cargo_toml_dependencies_patches() {
+        let mut tasks = HashMap::new();
+        tasks.insert(
+            "task0".to_string(),
+            Task {
+                action_name: "task0".to_string(),
+                kind: "OpenWhisk".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let mut patches = BTreeMap::new();
+        patches.insert("openwhisk_macro".to_string(), PathBuf::from("/local/openwhisk_macro"));
+        patches.insert("sp-core".to_string(), PathBuf::from("/local/sp-core"));
+
+        let output = generate_cargo_toml_dependencies(&workflow, &patches);
+        assert!(!output.contains("regex"));
+
+        assert_eq!(
+            output,
+            "
+openwhisk_macro = \"0.1.6\"
+
+
+[patch.crates-io]
+openwhisk_macro = { path = \"/local/openwhisk_macro\" }
+
+[patch.\"https://github.com/paritytech/substrate.git\"]
+sp-core = { path = \"/local/sp-core\" }
+"
+        );
+
+        assert_eq!(
+            generate_cargo_toml_dependencies(&Workflow::default(), &BTreeMap::new()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_generate_cargo_config() {
+        let mut tasks = HashMap::new();
+        tasks.insert(
+            "task0".to_string(),
+            Task {
+                action_name: "task0".to_string(),
+                kind: "hello_world".to_string(),
+                ..Default::default()
+            },
+        );
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let output = generate_cargo_config(&workflow, Path::new("/package/vendor"));
+
+        assert_eq!(
+            output,
+            "[source.crates-io]
+replace-with = \"vendored-sources\"
+
+[source.\"git+https://github.com/HugoByte/aurras.git?branch=next\"]
+git = \"https://github.com/HugoByte/aurras.git\"
+branch = \"next\"
+replace-with = \"vendored-sources\"
+
+[source.vendored-sources]
+directory = \"/package/vendor\"
+"
+        );
+    }
+
+    #[test]
+    fn test_get_impl_invoke_code() {
+        let mut tasks = HashMap::new();
+        tasks.insert(
+            "employee_ids".to_string(),
+            Task {
+                kind: "openwhisk".to_string(),
+                timeout_ms: Some(5000),
+                ..Default::default()
+            },
+        );
+        tasks.insert(
+            "getsalaries".to_string(),
+            Task {
+                kind: "openwhisk".to_string(),
+                ..Default::default()
+            },
+        );
+        tasks.insert(
+            "notify".to_string(),
+            Task {
+                kind: "polkadot".to_string(),
+                timeout_ms: Some(1000),
+                ..Default::default()
+            },
+        );
+
+        let mut workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_impl_invoke_code(&workflow),
+            "impl_invoke!(EmployeeIds, 5000);\nimpl_invoke!(Getsalaries);\nimpl_invoke!(Notify);\n"
+        );
+
+        workflow.timeout_ms = Some(3000);
+
+        assert_eq!(
+            get_impl_invoke_code(&workflow),
+            "impl_invoke!(EmployeeIds, 3000);\nimpl_invoke!(Getsalaries, 3000);\nimpl_invoke!(Notify);\n"
         );
     }
 }
@@ -1,6 +1,8 @@
 use super::*;
 use anyhow::anyhow;
-#[allow(clippy::type_complexity)]
+use starlark::values::none::NoneType;
+use starlark::values::ValueLike;
+#[allow(clippy::type_complexity, clippy::too_many_arguments)]
 #[starlark_module]
 pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// Creates a new task of the workflow and returns a task object of `Task` type
@@ -15,6 +17,12 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// * `operation` - An optional argument to mention type of the task operation
     /// * `depend_on` - The dependencies of the task
     ///   (i.e "map", "concat")
+    /// * `compensate` - An optional task executed to undo this task when a later task fails
+    /// * `timeout_ms` - An optional maximum time in milliseconds the task may run, checked
+    ///   once the task returns as a running task cannot be interrupted
+    /// * `retry` - An optional number of times the task is run again when it fails with a
+    ///   retryable error
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
     ///
     /// # Returns
     ///
@@ -27,21 +35,29 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         attributes: Option<Value>,
         operation: Option<Value>,
         depend_on: Option<Value>,
+        compensate: Option<Value>,
+        timeout_ms: Option<i32>,
+        retry: Option<i32>,
+        eval: &mut Evaluator,
     ) -> anyhow::Result<Task> {
-        if kind == "openwhisk" || kind == "polkadot" {
-            if attributes.is_none() {
-                return Err(anyhow!("Attributes are mandatory for kind: openwhisk or polkadot"));
-            }
-        }
-        
+        let schema = get_kind(&kind)?;
+        let kind = schema.name.to_string();
+
+        validate_task_name(&action_name)?;
+
         let mut input_arguments: Vec<Input> = serde_json::from_str(&input_arguments.to_json()?)
             .map_err(|err| anyhow!("Failed to parse input arguments: {}", err))?;
 
-        let attributes: HashMap<String, String> = match attributes {
+        let attributes: HashMap<String, serde_json::Value> = match attributes {
             Some(attributes) => serde_json::from_str(&attributes.to_json()?)
                 .map_err(|err| anyhow!("Failed to parse the attributes: {}", err))?,
             _ => HashMap::default(),
         };
+        // The required attributes are checked by `workflows()`, once the defaults of the kind
+        // are merged
+        let attributes = schema
+            .normalize(attributes)
+            .map_err(|err| anyhow!("Task {}: {}", action_name, err))?;
 
         let depend_on: Vec<Depend> = match depend_on {
             Some(val) => serde_json::from_str(&val.to_json()?)
@@ -49,11 +65,41 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
             None => Vec::default(),
         };
 
-        for depend in depend_on.iter() {
-            for argument in input_arguments.iter_mut() {
-                if argument.name == depend.cur_field {
-                    argument.is_depend = true;
-                    break;
+        let mut depend_on = depend_on;
+
+        for depend in depend_on.iter_mut() {
+            // `other.output("field")` feeds the input field of the same name
+            if depend.cur_field.is_empty() {
+                depend.cur_field = depend.prev_field.clone();
+            }
+
+            match input_arguments
+                .iter_mut()
+                .find(|argument| argument.name == depend.cur_field)
+            {
+                Some(argument) if !argument.constraints.is_empty() => {
+                    return Err(anyhow!(
+                        "Task {}: argument {} is fed by a dependency and cannot have constraints, \
+                        they are only checked on the input of the workflow",
+                        action_name,
+                        argument.name
+                    ));
+                }
+                Some(argument) => argument.is_depend = true,
+                None => {
+                    let names: Vec<String> = input_arguments
+                        .iter()
+                        .map(|argument| argument.name.clone())
+                        .collect();
+
+                    return Err(anyhow!(
+                        "Task {}: dependency field {} is not an input argument of the task{}",
+                        action_name,
+                        depend.cur_field,
+                        get_similar_name(&depend.cur_field, names.iter())
+                            .map(|name| format!(", did you mean {}?", name))
+                            .unwrap_or_default()
+                    ));
                 }
             }
         }
@@ -64,14 +110,33 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
             _ => Operation::Normal,
         };
 
-        Ok(Task {
+        let compensate: Option<Box<Task>> = match compensate {
+            Some(task) => Some(Box::new(
+                serde_json::from_str(&task.to_json()?)
+                    .map_err(|err| anyhow!("Failed to parse the compensation task: {}", err))?,
+            )),
+            None => None,
+        };
+
+        let timeout_ms = timeout_ms.map(get_timeout).transpose()?;
+        let retry = retry.map(get_retry).transpose()?;
+
+        let task = Task {
             kind,
             action_name,
             input_arguments,
             attributes,
             operation,
             depend_on,
-        })
+            compensate,
+            timeout_ms,
+            retry,
+            span: get_span(eval),
+        };
+
+        get_composer(eval)?.tasks.borrow_mut().push(task.clone());
+
+        Ok(task)
     }
 
     /// Creates and adds a new workflow to the composer
@@ -82,7 +147,8 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// * `name` - A string that holds the name of the workflow
     /// * `version` - A string that holds the version of the workflow
     /// * `tasks` - The tasks of the workflow
-    /// * `custom_types` - Optional custom types for the workflow
+    /// * `timeout_ms` - An optional maximum time in milliseconds the workflow may run,
+    ///   checked each time a task returns
     /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
     ///
     /// # Returns
@@ -93,33 +159,51 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         name: String,
         version: String,
         tasks: Value,
+        timeout_ms: Option<i32>,
         eval: &mut Evaluator,
     ) -> anyhow::Result<Workflow> {
         let tasks: Vec<Task> = serde_json::from_str(&tasks.to_json()?)
             .map_err(|err| anyhow!("Failed to parse task value: {}", err))?;
 
+        let composer = get_composer(eval)?;
         let mut task_hashmap = HashMap::new();
 
-        for task in tasks {
+        for mut task in tasks {
+            task.apply_defaults(&composer.task_defaults.borrow());
+            check_required_attributes(&task)?;
+
             if task_hashmap.contains_key(&task.action_name) {
-                return Err(Error::msg("Duplicate tasks, Task names must be unique"));
+                return Err(Error::msg(with_span(
+                    "Duplicate tasks, Task names must be unique",
+                    task.span.as_ref(),
+                )));
             } else {
                 task_hashmap.insert(task.action_name.clone(), task);
             }
         }
 
-        eval.extra
-            .as_ref()
-            .and_then(|extra| extra.downcast_ref::<Composer>())
-            .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))?
-            .add_workflow(name.clone(), version.clone(), task_hashmap.clone())
-            .map_err(|err| anyhow!("Failed to add workflow: {}", err))?;
-
-        Ok(Workflow {
+        let workflow = Workflow {
             name,
             version,
             tasks: task_hashmap,
-        })
+            timeout_ms: timeout_ms.map(get_timeout).transpose()?,
+        };
+
+        workflow
+            .validate()
+            .and_then(|_| validate_identifiers(&workflow, composer.custom_types.borrow().keys()))
+            .map_err(|err| anyhow!("Invalid workflow {}: {}", workflow.name, err))?;
+
+        composer
+            .add_workflow(
+                workflow.name.clone(),
+                workflow.version.clone(),
+                workflow.tasks.clone(),
+                workflow.timeout_ms,
+            )
+            .map_err(|err| anyhow!("Failed to add workflow: {}", err))?;
+
+        Ok(workflow)
     }
 
     /// Creates a new field for the input argument of a task
@@ -129,6 +213,13 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     /// * `name` - A string that holds the name of the input field
     /// * `input_type` - A string that holds the type of the input field
     /// * `default_value` - An optional JSON default value for the input field
+    /// * `min` - An optional lower bound of a numeric field
+    /// * `max` - An optional upper bound of a numeric field
+    /// * `pattern` - An optional regular expression a string field has to match
+    /// * `one_of` - An optional list of the values allowed for the field
+    /// * `min_len` - An optional minimum length of a string, list or map field
+    /// * `non_empty` - Whether a string, list or map field must not be empty
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
     ///
     /// # Returns
     ///
@@ -138,7 +229,16 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
         name: String,
         input_type: Value,
         default_value: Option<Value>,
+        min: Option<Value>,
+        max: Option<Value>,
+        pattern: Option<String>,
+        one_of: Option<Value>,
+        min_len: Option<i32>,
+        non_empty: Option<bool>,
+        eval: &mut Evaluator,
     ) -> anyhow::Result<Input> {
+        validate_argument_name(&name)?;
+
         let input_type: RustType = serde_json::from_str(&input_type.to_json()?)
             .map_err(|err| anyhow!("Failed to parse input arguments: {}", err))?;
 
@@ -183,22 +283,186 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
             None => Default::default(),
         };
 
+        let mut constraints = Vec::new();
+
+        if let Some(min) = min {
+            constraints.push(Constraint::Min(get_number(&name, "min", min)?));
+        }
+
+        if let Some(max) = max {
+            constraints.push(Constraint::Max(get_number(&name, "max", max)?));
+        }
+
+        if let Some(pattern) = pattern {
+            constraints.push(Constraint::Pattern(pattern));
+        }
+
+        if let Some(one_of) = one_of {
+            let values: Vec<serde_json::Value> = serde_json::from_str(&one_of.to_json()?)
+                .map_err(|err| anyhow!("Argument {}: one_of must be a list: {}", name, err))?;
+
+            if values.is_empty() {
+                return Err(anyhow!("Argument {}: one_of must not be empty", name));
+            }
+
+            constraints.push(Constraint::OneOf(
+                values.iter().map(|value| value.to_string()).collect(),
+            ));
+        }
+
+        if let Some(min_len) = min_len {
+            let min_len = usize::try_from(min_len)
+                .map_err(|_| anyhow!("Argument {}: min_len must not be negative", name))?;
+            constraints.push(Constraint::MinLen(min_len));
+        }
+
+        if non_empty == Some(true) {
+            constraints.push(Constraint::NonEmpty);
+        }
+
+        for constraint in constraints.iter() {
+            constraint
+                .validate_type(&input_type)
+                .map_err(|err| anyhow!("Argument {}: {}", name, err))?;
+
+            if let Some(value) = default_value.as_ref() {
+                let value: serde_json::Value = serde_json::from_str(value)
+                    .map_err(|err| anyhow!("Failed to parse default value: {}", err))?;
+
+                constraint.check(&value).map_err(|err| {
+                    anyhow!("Argument {}: default {}", name, err)
+                })?;
+            }
+        }
+
         Ok(Input {
             name,
             input_type,
             default_value,
             is_depend: false,
+            constraints,
+            span: get_span(eval),
         })
     }
 
-    fn depend(task_name: String, cur_field: String, prev_field: String) -> anyhow::Result<Depend> {
+    /// Creates a dependency of a task on the output of another task
+    /// This method will be invoked inside the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `task_name` - An optional string that holds the action name of the task depended on
+    /// * `cur_field` - A string that holds the input field of the task receiving the output
+    /// * `prev_field` - A string that holds the output field of the task depended on
+    /// * `task` - An optional task object returned by `task()`, given instead of `task_name`
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing the dependency, or an error if neither or both of `task_name`
+    ///   and `task` are given
+    ///
+    fn depend(
+        task_name: Option<String>,
+        cur_field: String,
+        prev_field: String,
+        #[starlark(require = named)] task: Option<Value>,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<Depend> {
+        let task_name = match (task_name, task) {
+            (Some(task_name), None) => task_name,
+            (None, Some(task)) => get_task(task)?.action_name.clone(),
+            (Some(_), Some(_)) => {
+                return Err(anyhow!("depend() takes either task_name or task, not both"))
+            }
+            (None, None) => return Err(anyhow!("depend() needs either task_name or task")),
+        };
+
         Ok(Depend {
             task_name,
             cur_field,
             prev_field,
+            span: get_span(eval),
         })
     }
 
+    /// Sets the defaults of the tasks of a kind, which are merged into every task of the kind
+    /// when it is added to a workflow. Attributes and values set on a task override them.
+    /// This method will be invoked inside the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - A string that holds the kind of the tasks (i.e "polkadot", "openwhisk")
+    /// * `attributes` - The default attributes of the tasks
+    /// * `retry` - An optional default number of times a task is run again when it fails
+    ///   with a retryable error
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing None, or an error if the kind is unknown, an attribute does not
+    ///   match the schema of the kind or the defaults of the kind are already set
+    ///
+    fn defaults(
+        kind: String,
+        attributes: Option<Value>,
+        retry: Option<i32>,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        let schema = get_kind(&kind)?;
+
+        let attributes: HashMap<String, serde_json::Value> = match attributes {
+            Some(attributes) => serde_json::from_str(&attributes.to_json()?)
+                .map_err(|err| anyhow!("Failed to parse the attributes: {}", err))?,
+            _ => HashMap::default(),
+        };
+        let attributes = schema
+            .normalize(attributes)
+            .map_err(|err| anyhow!("Defaults of kind {}: {}", schema.name, err))?;
+
+        let defaults = TaskDefaults {
+            attributes,
+            retry: retry.map(get_retry).transpose()?,
+        };
+
+        let mut task_defaults = get_composer(eval)?.task_defaults.borrow_mut();
+
+        if task_defaults.contains_key(schema.name) {
+            return Err(anyhow!("The defaults of kind {} are already set", schema.name));
+        }
+
+        task_defaults.insert(schema.name.to_string(), defaults);
+
+        Ok(NoneType)
+    }
+
+    /// Suppresses the findings of a lint rule for the package, or for one of its tasks
+    /// This method will be invoked inside the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - A string that holds the name of the lint rule (i.e "unused_task")
+    /// * `task` - An optional name of the task the rule is suppressed for
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing None, or an error if there is no rule with this name
+    ///
+    fn allow_lint(
+        rule: String,
+        task: Option<String>,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<NoneType> {
+        get_lint_rule(&rule)?;
+
+        get_composer(eval)?
+            .lint_suppressions
+            .borrow_mut()
+            .push(Suppression { rule, task });
+
+        Ok(NoneType)
+    }
+
     /// Creates a user-defined type inside the `types.rs`.
     /// This method will be invoked inside the config file.
     ///
@@ -246,6 +510,143 @@ pub fn starlark_workflow_module(builder: &mut GlobalsBuilder) {
     }
 }
 
+#[allow(clippy::type_complexity)]
+#[starlark_module]
+pub fn starlark_task_methods(builder: &mut MethodsBuilder) {
+    /// Creates a dependency on an output field of the task, to be given in the `depend_on` of
+    /// another task, e.g. `modelprice.output("models")`
+    /// This method will be invoked inside the config file.
+    ///
+    /// # Arguments
+    ///
+    /// * `this` - The task depended on
+    /// * `field` - A string that holds the output field of the task
+    /// * `cur_field` - An optional input field of the dependent task receiving the output,
+    ///   the input field with the same name as `field` if omitted
+    /// * `eval` - A mutable reference to the Evaluator (injected by the starlark rust package)
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing the dependency, whose input field is resolved by `task()`
+    ///
+    fn output(
+        this: Value,
+        field: String,
+        cur_field: Option<String>,
+        eval: &mut Evaluator,
+    ) -> anyhow::Result<Depend> {
+        Ok(Depend {
+            task_name: get_task(this)?.action_name.clone(),
+            cur_field: cur_field.unwrap_or_default(),
+            prev_field: field,
+            span: get_span(eval),
+        })
+    }
+}
+
+/// Returns the task object held by the given value
+fn get_task<'v>(value: Value<'v>) -> anyhow::Result<&'v Task> {
+    value
+        .downcast_ref::<Task>()
+        .ok_or_else(|| anyhow!("Expected a task returned by task(), got {}", value.get_type()))
+}
+
+/// Returns the composer evaluating the config file
+fn get_composer<'a>(eval: &'a Evaluator) -> anyhow::Result<&'a Composer> {
+    eval.extra
+        .as_ref()
+        .and_then(|extra| extra.downcast_ref::<Composer>())
+        .ok_or_else(|| anyhow!("Failed to obtain Composer from Evaluator"))
+}
+
+/// Returns the location of the call of the config file to the running builtin. When the
+/// builtin is called by a helper of the standard library, the call of the helper is used
+/// instead, as the config file is where the mistake can be fixed.
+fn get_span(eval: &Evaluator) -> Option<Span> {
+    eval.call_stack()
+        .frames
+        .into_iter()
+        .rev()
+        .filter_map(|frame| frame.location)
+        .find(|location| !location.filename().starts_with(STD_PREFIX))
+        .or_else(|| eval.call_stack_top_location())
+        .map(Span::from)
+}
+
+/// Returns the JSON representation of the numeric bound of a constraint
+fn get_number(name: &str, rule: &str, value: Value) -> anyhow::Result<String> {
+    let value = value.to_json()?;
+
+    if !value.parse::<f64>().is_ok_and(f64::is_finite) {
+        return Err(anyhow!("Argument {}: {} must be a number", name, rule));
+    }
+
+    Ok(value)
+}
+
+/// Converts the timeout given in the config file to milliseconds
+///
+/// # Arguments
+///
+/// * `timeout_ms` - The timeout in milliseconds
+///
+/// # Returns
+///
+/// * A Result containing the timeout, or an error if the timeout is not a positive integer
+///
+fn get_timeout(timeout_ms: i32) -> anyhow::Result<u64> {
+    match u64::try_from(timeout_ms) {
+        Ok(timeout_ms) if timeout_ms > 0 => Ok(timeout_ms),
+        _ => Err(anyhow!("timeout_ms must be a positive integer")),
+    }
+}
+
+/// Returns the schema of the given kind, or an error listing the known kinds
+fn get_kind(kind: &str) -> anyhow::Result<&'static KindSchema> {
+    get_kind_schema(kind).ok_or_else(|| {
+        anyhow!(
+            "Unknown kind {}, expected one of: {}",
+            kind,
+            KINDS
+                .iter()
+                .map(|schema| schema.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    })
+}
+
+/// Returns the number of retries of a task
+///
+/// # Arguments
+///
+/// * `retry` - The number of retries given in the config file
+///
+/// # Returns
+///
+/// * A Result containing the number of retries, or an error if it is negative
+///
+fn get_retry(retry: i32) -> anyhow::Result<u32> {
+    u32::try_from(retry).map_err(|_| anyhow!("retry must be a non-negative integer"))
+}
+
+/// Checks that a task, and its compensation task, hold the attributes their kind requires
+fn check_required_attributes(task: &Task) -> anyhow::Result<()> {
+    if let Some(schema) = get_kind_schema(&task.kind) {
+        schema.check_required(&task.attributes).map_err(|err| {
+            anyhow!(with_span(
+                format!("Task {}: {}", task.action_name, err),
+                task.span.as_ref()
+            ))
+        })?;
+    }
+
+    match task.compensate.as_ref() {
+        Some(compensate) => check_required_attributes(compensate),
+        None => Ok(()),
+    }
+}
+
 #[starlark_module]
 pub fn starlark_datatype_module(builder: &mut GlobalsBuilder) {
     /// Returns the Rust type for a tuple with specified types of the key and vale
@@ -357,4 +758,49 @@ pub fn starlark_operation_module(builder: &mut GlobalsBuilder) {
     fn map(field: String) -> anyhow::Result<Operation> {
         Ok(Operation::Map(field))
     }
+
+    /// Returns `Operation::RepeatUntil(repeat)` task-operation type to the config file
+    /// This method will be invoked inside the config file
+    ///
+    /// # Arguments
+    ///
+    /// * `field` - A String containing name of the output field that is checked after every
+    ///   iteration
+    /// * `value` - The value of the output field on which the loop stops
+    /// * `max_iterations` - The maximum number of times the task is executed
+    /// * `feedback` - An optional dict mapping the input fields of the task to the output
+    ///   fields of the previous iteration
+    ///
+    /// # Returns
+    ///
+    /// * A Result containing Operation::RepeatUntil(repeat)
+    ///
+    fn repeat_until(
+        field: String,
+        value: Value,
+        max_iterations: i32,
+        feedback: Option<Value>,
+    ) -> anyhow::Result<Operation> {
+        let max_iterations = u32::try_from(max_iterations)
+            .ok()
+            .filter(|max_iterations| *max_iterations > 0)
+            .ok_or_else(|| anyhow!("max_iterations must be a positive integer"))?;
+
+        let value = value
+            .to_json()
+            .map_err(|err| anyhow!("Failed to parse the loop condition value: {}", err))?;
+
+        let feedback: HashMap<String, String> = match feedback {
+            Some(feedback) => serde_json::from_str(&feedback.to_json()?)
+                .map_err(|err| anyhow!("Failed to parse the loop feedback: {}", err))?,
+            None => HashMap::default(),
+        };
+
+        Ok(Operation::RepeatUntil(Repeat {
+            field,
+            value,
+            max_iterations,
+            feedback,
+        }))
+    }
 }
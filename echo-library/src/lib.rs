@@ -4,17 +4,17 @@ use anyhow::Error;
 use convert_case::{Case, Casing};
 use serde_derive::{Deserialize, Serialize};
 use starlark::environment::LibraryExtension::*;
-use starlark::environment::{GlobalsBuilder, Module};
+use starlark::environment::{GlobalsBuilder, Methods, MethodsBuilder, MethodsStatic, Module};
 use starlark::eval::Evaluator;
 use starlark::syntax::{AstModule, Dialect};
 use starlark::values::{ProvidesStaticType, StarlarkValue, Value};
 use starlark::{starlark_module, starlark_simple_value, values::starlark_value};
 use std::cell::RefCell;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::{self, Display};
 use std::fs;
 use std::io::ErrorKind;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::result::Result::Ok;
 
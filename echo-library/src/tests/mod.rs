@@ -1,6 +1,8 @@
 #[cfg(test)]
+#[allow(clippy::module_inception, clippy::vec_init_then_push)]
 mod tests {
     use super::super::*;
+    use composer_primitives::{Manifest, SourceFiles};
 
     #[test]
     fn add_workflow_test_pass() {
@@ -10,6 +12,7 @@ mod tests {
             name: "test-workflow".to_string(),
             version: "0.0.1".to_string(),
             tasks: HashMap::default(),
+            ..Default::default()
         };
 
         composer
@@ -17,6 +20,7 @@ mod tests {
                 "test-workflow".to_string(),
                 "0.0.1".to_string(),
                 HashMap::default(),
+                None,
             )
             .unwrap();
 
@@ -25,6 +29,44 @@ mod tests {
         assert_eq!(composer_workflow, &workflow1);
     }
 
+    #[test]
+    fn add_workflow_versions_test() {
+        let composer = Composer::default();
+        let add = |name: &str, version: &str| {
+            composer
+                .add_workflow(
+                    name.to_string(),
+                    version.to_string(),
+                    HashMap::default(),
+                    None,
+                )
+                .map_err(|err| err.to_string())
+        };
+
+        add("payout", "1.0.0").unwrap();
+        add("payout", "2.0.0-beta.1").unwrap();
+
+        assert_eq!(
+            add("payout", "1.0.0").unwrap_err(),
+            "Workflows should not have same name and version, found payout 1.0.0 twice"
+        );
+        assert!(add("payout", "v1")
+            .unwrap_err()
+            .starts_with("Invalid version \"v1\" of workflow payout, expected a semantic version"));
+        assert_eq!(
+            add("../payout", "1.0.0").unwrap_err(),
+            "Invalid workflow name \"../payout\", it must start with a letter and contain only \
+            letters, digits, `_` and `-`"
+        );
+        add("payout-v2", "1.0.0").unwrap();
+        assert_eq!(
+            add("payout_v2", "1.0.0").unwrap_err(),
+            "Workflows payout-v2 1.0.0 and payout_v2 1.0.0 generate the same package \
+            boilerplate_payout_v2_1_0_0, rename one of them"
+        );
+        assert_eq!(composer.workflows.borrow().len(), 3);
+    }
+
     #[test]
     fn get_dependencies_test() {
         let composer = Composer::default();
@@ -34,6 +76,7 @@ mod tests {
             task_name: "dependent_task".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
 
         let task = Task {
@@ -46,7 +89,12 @@ mod tests {
         tasks.insert("get_salaries".to_string(), task);
 
         composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+            .add_workflow(
+                "test-workflow".to_string(),
+                "0.0.1".to_string(),
+                tasks,
+                None,
+            )
             .unwrap();
 
         assert_eq!(
@@ -75,11 +123,13 @@ mod tests {
             task_name: "task0".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         dependencies.push(Depend {
             task_name: "task4".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         task1.depend_on = dependencies;
 
@@ -93,6 +143,7 @@ mod tests {
             task_name: "task0".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         task2.depend_on = dependencies;
 
@@ -106,11 +157,13 @@ mod tests {
             task_name: "task1".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         dependencies.push(Depend {
             task_name: "task2".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         task3.depend_on = dependencies;
 
@@ -128,6 +181,7 @@ mod tests {
             task_name: "task2".to_string(),
             cur_field: "id".to_string(),
             prev_field: "ids".to_string(),
+            ..Default::default()
         });
         task5.depend_on = dependencies;
 
@@ -140,7 +194,12 @@ mod tests {
         tasks.insert("task5".to_string(), task5);
 
         composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+            .add_workflow(
+                "test-workflow".to_string(),
+                "0.0.1".to_string(),
+                tasks,
+                None,
+            )
             .unwrap();
 
         let flow = composer.workflows.borrow()[0].get_flow();
@@ -162,6 +221,1637 @@ mod tests {
         assert!(flow[5] == "task3" || flow[5] == "task5");
     }
 
+    #[test]
+    fn get_sinks_test() {
+        let task0 = Task {
+            action_name: "task0".to_string(),
+            ..Default::default()
+        };
+
+        let task1 = Task {
+            action_name: "task1".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                cur_field: "id".to_string(),
+                prev_field: "ids".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let task2 = Task {
+            action_name: "task2".to_string(),
+            depend_on: vec![Depend {
+                task_name: "task0".to_string(),
+                cur_field: "id".to_string(),
+                prev_field: "ids".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("task0".to_string(), task0);
+        tasks.insert("task1".to_string(), task1);
+        tasks.insert("task2".to_string(), task2);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let flow = workflow.get_flow();
+        let sinks = workflow.get_sinks(&flow);
+
+        assert_eq!(sinks.len(), 2);
+        assert!(sinks.contains(&"task1".to_string()));
+        assert!(sinks.contains(&"task2".to_string()));
+    }
+
+    #[test]
+    fn validate_workflow_compensation_test() {
+        let refund = Task {
+            action_name: "refund".to_string(),
+            depend_on: vec![Depend {
+                task_name: "transfer".to_string(),
+                cur_field: "tx_hash".to_string(),
+                prev_field: "tx_hash".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let transfer = Task {
+            action_name: "transfer".to_string(),
+            compensate: Some(Box::new(refund.clone())),
+            ..Default::default()
+        };
+
+        let workflow_of = |tasks: Vec<Task>| Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks: tasks
+                .into_iter()
+                .map(|task| (task.action_name.clone(), task))
+                .collect(),
+            ..Default::default()
+        };
+
+        assert!(workflow_of(vec![transfer.clone()]).validate().is_ok());
+
+        // The compensation depends on a task which is not the one it compensates
+        let mut unknown = refund.clone();
+        unknown.depend_on[0].task_name = "payment".to_string();
+        let error = workflow_of(vec![Task {
+            compensate: Some(Box::new(unknown)),
+            ..transfer.clone()
+        }])
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task transfer: compensation task refund can only depend on the task it compensates"
+        );
+
+        // A task compensating itself
+        let error = workflow_of(vec![Task {
+            compensate: Some(Box::new(Task {
+                action_name: "transfer".to_string(),
+                ..Default::default()
+            })),
+            ..transfer.clone()
+        }])
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task transfer: compensation task transfer must not be a task of the workflow"
+        );
+
+        // Two tasks sharing a compensation task which does not depend on either of them
+        let notify = Task {
+            action_name: "notify".to_string(),
+            ..Default::default()
+        };
+        let payment = Task {
+            action_name: "payment".to_string(),
+            compensate: Some(Box::new(notify.clone())),
+            ..Default::default()
+        };
+        let shared = workflow_of(vec![
+            Task {
+                compensate: Some(Box::new(notify.clone())),
+                ..transfer.clone()
+            },
+            payment.clone(),
+        ]);
+        assert!(shared.validate().is_ok());
+        assert_eq!(shared.with_compensations().unwrap().tasks.len(), 3);
+
+        // Two different compensation tasks with the same name
+        let error = workflow_of(vec![
+            transfer.clone(),
+            Task {
+                compensate: Some(Box::new(Task {
+                    action_name: "refund".to_string(),
+                    ..Default::default()
+                })),
+                ..payment
+            },
+        ])
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task transfer: compensation task refund has the name of another task of workflow \
+            test-workflow"
+        );
+
+        let error = workflow_of(vec![transfer.clone(), refund.clone()])
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task transfer: compensation task refund must not be a task of the workflow"
+        );
+
+        let error = workflow_of(vec![Task {
+            compensate: Some(Box::new(Task {
+                compensate: Some(Box::new(Task {
+                    action_name: "audit".to_string(),
+                    ..Default::default()
+                })),
+                ..refund
+            })),
+            ..transfer
+        }])
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Task transfer: compensation task refund must not have a compensation"
+        );
+    }
+
+    #[test]
+    fn validate_workflow_cycle_test() {
+        let task_of = |action_name: &str, depends: &[&str]| Task {
+            action_name: action_name.to_string(),
+            depend_on: depends
+                .iter()
+                .map(|task_name| Depend {
+                    task_name: task_name.to_string(),
+                    cur_field: "x".to_string(),
+                    prev_field: "x".to_string(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        };
+
+        let workflow_of = |tasks: Vec<Task>| Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks: tasks
+                .into_iter()
+                .map(|task| (task.action_name.clone(), task))
+                .collect(),
+            ..Default::default()
+        };
+
+        // A diamond is not a cycle
+        assert!(workflow_of(vec![
+            task_of("a", &[]),
+            task_of("b", &["a"]),
+            task_of("c", &["a"]),
+            task_of("d", &["b", "c"]),
+        ])
+        .validate()
+        .is_ok());
+
+        let error = workflow_of(vec![
+            task_of("a", &[]),
+            task_of("b", &["a", "d"]),
+            task_of("c", &["b"]),
+            task_of("d", &["c"]),
+        ])
+        .validate()
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Tasks b -> d -> c -> b depend on each other in a cycle"
+        );
+
+        let error = workflow_of(vec![task_of("a", &["a"])])
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Tasks a -> a depend on each other in a cycle"
+        );
+    }
+
+    #[test]
+    fn validate_workflow_repeat_until_test() {
+        let mut feedback = HashMap::new();
+        feedback.insert("page".to_string(), "next_page".to_string());
+
+        let mut task = Task {
+            action_name: "get_page".to_string(),
+            input_arguments: vec![Input {
+                name: "page".to_string(),
+                input_type: RustType::Int,
+                ..Default::default()
+            }],
+            operation: Operation::RepeatUntil(Repeat {
+                field: "done".to_string(),
+                value: "true".to_string(),
+                max_iterations: 5,
+                feedback,
+            }),
+            ..Default::default()
+        };
+
+        let mut tasks = HashMap::new();
+        tasks.insert("get_page".to_string(), task.clone());
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        assert!(workflow.validate().is_ok());
+
+        task.input_arguments[0].name = "offset".to_string();
+
+        let mut tasks = HashMap::new();
+        tasks.insert("get_page".to_string(), task);
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        assert!(workflow.validate().is_err());
+    }
+
+    #[test]
+    fn constraint_test() {
+        let min = Constraint::Min("1".to_string());
+        assert!(min.validate_type(&RustType::Int).is_ok());
+        assert!(min.validate_type(&RustType::String).is_err());
+        assert!(min.check(&serde_json::json!(1)).is_ok());
+        assert!(min.check(&serde_json::json!(0.5)).is_err());
+
+        let max = Constraint::Max("10".to_string());
+        assert!(max.check(&serde_json::json!(10)).is_ok());
+        assert!(max.check(&serde_json::json!(11)).is_err());
+
+        let pattern = Constraint::Pattern("^[a-z]+$".to_string());
+        assert!(pattern.validate_type(&RustType::String).is_ok());
+        assert!(pattern.check(&serde_json::json!("abc")).is_ok());
+        assert!(pattern.check(&serde_json::json!("ABC")).is_err());
+        assert!(Constraint::Pattern("(".to_string())
+            .validate_type(&RustType::String)
+            .is_err());
+
+        let one_of = Constraint::OneOf(vec!["\"dev\"".to_string(), "\"prod\"".to_string()]);
+        assert!(one_of.check(&serde_json::json!("dev")).is_ok());
+        assert_eq!(
+            one_of.check(&serde_json::json!("test")).unwrap_err(),
+            "value \"test\" does not satisfy one_of = [\"dev\", \"prod\"]"
+        );
+
+        // Numbers are compared by value
+        let one_of = Constraint::OneOf(vec!["1".to_string(), "0.1".to_string()]);
+        assert!(one_of.check(&serde_json::json!(1.0)).is_ok());
+        assert!(one_of.check(&serde_json::json!(0.1)).is_ok());
+        assert!(one_of.check(&serde_json::json!("1")).is_err());
+
+        let min_len = Constraint::MinLen(2);
+        assert!(min_len
+            .validate_type(&RustType::List(Box::new(RustType::Int)))
+            .is_ok());
+        assert!(min_len.validate_type(&RustType::Boolean).is_err());
+        assert!(min_len.check(&serde_json::json!([1, 2])).is_ok());
+        assert!(min_len.check(&serde_json::json!("a")).is_err());
+
+        assert!(Constraint::NonEmpty
+            .check(&serde_json::json!({"a": 1}))
+            .is_ok());
+        assert!(Constraint::NonEmpty.check(&serde_json::json!("")).is_err());
+
+        let root =
+            std::env::temp_dir().join(format!("composer-constraint-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+
+        for (bound, rule) in [("\"one\"", "min"), ("float(\"inf\")", "max")] {
+            fs::write(
+                root.join("main.echo"),
+                format!(
+                    "a = task(kind = \"hello_world\", action_name = \"a\", input_arguments = [argument(name = \"amount\", input_type = Int, {} = {})])\n",
+                    rule, bound
+                ),
+            )
+            .unwrap();
+            let error = Composer::default()
+                .compile("main.echo", &files)
+                .unwrap_err()
+                .to_string();
+            assert!(error.contains(&format!("Argument amount: {} must be a number", rule)));
+        }
+
+        fs::write(
+            root.join("main.echo"),
+            "a = task(kind = \"hello_world\", action_name = \"a\", input_arguments = [argument(name = \"rate\", input_type = Float, one_of = [1, 0.5], default_value = 1)])\nb = task(kind = \"hello_world\", action_name = \"b\", input_arguments = [argument(name = \"amount\", input_type = Int, min = 1)], depend_on = [depend(task_name = \"a\", cur_field = \"amount\", prev_field = \"amount\")])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Task b: argument amount is fed by a dependency and cannot have constraints"
+        ));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn get_compiler_errors_test() {
+        let messages = [
+            r#"{"reason":"compiler-artifact","package_id":"serde 1.0.192"}"#,
+            r#"{"reason":"compiler-message","message":{"level":"warning","message":"unused variable","rendered":"warning: unused variable","spans":[]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"mismatched types","rendered":"error[E0308]: mismatched types","spans":[{"file_name":"src/types.rs","line_start":5,"is_primary":true,"expansion":null}]}}"#,
+            r#"{"reason":"compiler-message","message":{"level":"error","message":"no field `b` on type `IncInput`","rendered":"error[E0609]: no field `b`","spans":[{"file_name":"src/macros.rs","line_start":120,"is_primary":true,"expansion":{"span":{"file_name":"src/types.rs","line_start":17,"expansion":null}}}]}}"#,
+            r#"{"reason":"build-finished","success":false}"#,
+        ]
+        .join("\n");
+
+        assert_eq!(
+            get_compiler_errors(&messages),
+            vec![
+                Diagnostic {
+                    message: "mismatched types".to_string(),
+                    rendered: "error[E0308]: mismatched types".to_string(),
+                    line: Some(5),
+                },
+                Diagnostic {
+                    message: "no field `b` on type `IncInput`".to_string(),
+                    rendered: "error[E0609]: no field `b`".to_string(),
+                    line: Some(17),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn get_generated_code_source_test() {
+        let mut tasks = HashMap::new();
+        tasks.insert(
+            "transfer".to_string(),
+            Task {
+                action_name: "transfer".to_string(),
+                kind: "openwhisk".to_string(),
+                input_arguments: vec![Input {
+                    name: "amount".to_string(),
+                    input_type: RustType::Int,
+                    default_value: Some("10".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+        tasks.insert(
+            "notify".to_string(),
+            Task {
+                action_name: "notify".to_string(),
+                kind: "openwhisk".to_string(),
+                depend_on: vec![Depend {
+                    task_name: "transfer".to_string(),
+                    cur_field: "receipt".to_string(),
+                    prev_field: "hash".to_string(),
+                    ..Default::default()
+                }],
+                input_arguments: vec![Input {
+                    name: "receipt".to_string(),
+                    input_type: RustType::String,
+                    is_depend: true,
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+        );
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        let types_rs = generate_types_rs_file_code(&workflow, &HashMap::new()).unwrap();
+        let line_of = |text: &str| {
+            types_rs
+                .lines()
+                .position(|line| line.contains(text))
+                .unwrap()
+                + 1
+        };
+
+        assert_eq!(
+            get_generated_code_source(&workflow, &types_rs, line_of("pub fn amount_fn")),
+            Some("task transfer, argument amount".to_string())
+        );
+        assert_eq!(
+            get_generated_code_source(&workflow, &types_rs, line_of("NotifyInput,")),
+            Some("task notify".to_string())
+        );
+        assert_eq!(
+            get_generated_code_source(&workflow, &types_rs, line_of("receipt:\"hash\"")),
+            Some("task notify, argument receipt".to_string())
+        );
+        assert_eq!(get_generated_code_source(&workflow, &types_rs, 1), None);
+        assert_eq!(
+            get_generated_code_source(&workflow, &types_rs, 100_000),
+            None
+        );
+    }
+
+    #[test]
+    fn get_package_hash_test() {
+        let package_dir =
+            std::env::temp_dir().join(format!("composer-package-hash-test-{}", std::process::id()));
+        fs::create_dir_all(package_dir.join("src")).unwrap();
+        fs::write(package_dir.join("Cargo.toml"), "[package]").unwrap();
+        fs::write(package_dir.join("src/lib.rs"), "mod types;").unwrap();
+        fs::write(package_dir.join("src/types.rs"), "use super::*;").unwrap();
+
+        let hash = get_package_hash(&package_dir, "wasm32-wasi").unwrap();
+        assert_eq!(hash.len(), 16);
+        assert_eq!(hash, get_package_hash(&package_dir, "wasm32-wasi").unwrap());
+        assert_ne!(
+            hash,
+            get_package_hash(&package_dir, "wasm32-unknown-unknown").unwrap()
+        );
+
+        fs::write(package_dir.join("src/types.rs"), "use super::*;\n").unwrap();
+        let changed = get_package_hash(&package_dir, "wasm32-wasi").unwrap();
+        assert_ne!(hash, changed);
+
+        fs::write(package_dir.join("Cargo.lock"), "version = 3").unwrap();
+        let locked = get_package_hash(&package_dir, "wasm32-wasi").unwrap();
+        assert_ne!(changed, locked);
+        fs::remove_file(package_dir.join("Cargo.lock")).unwrap();
+
+        let toolchain = get_toolchain_version(&package_dir).unwrap();
+        assert!(toolchain.starts_with("rustc "));
+        assert_ne!(
+            changed,
+            get_package_hash(&package_dir, &format!("wasm32-wasi\n{toolchain}")).unwrap()
+        );
+
+        fs::remove_dir_all(&package_dir).unwrap();
+
+        let options = BuildOptions {
+            cache_dir: PathBuf::from("/cache"),
+            ..Default::default()
+        };
+        assert_eq!(options.target_dir(), PathBuf::from("/cache/target"));
+        assert_eq!(
+            options.cached_wasm(&changed),
+            PathBuf::from(format!("/cache/wasm/{changed}.wasm"))
+        );
+    }
+
+    #[test]
+    fn lock_file_merge_test() {
+        let cargo_lock: LockFile = r#"
+version = 3
+
+[[package]]
+name = "boilerplate_hello_0_0_1"
+version = "0.0.1"
+dependencies = [
+ "hello_world_macro",
+ "serde",
+]
+
+[[package]]
+name = "serde"
+version = "1.0.192"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "bca2a08484b285dcb282d0f67b26cadc0df8b19f8c12502c13d966bf9482f001"
+
+[[package]]
+name = "hello_world_macro"
+version = "0.1.0"
+source = "git+https://github.com/HugoByte/aurras.git?branch=next#1f2d3c4"
+"#
+        .parse()
+        .unwrap();
+
+        let mut lock = LockFile::default();
+        lock.merge(&cargo_lock, "boilerplate_hello_0_0_1");
+        lock.merge(&cargo_lock, "boilerplate_hello_0_0_1");
+
+        assert_eq!(lock.version, 3);
+        assert_eq!(
+            lock.packages
+                .iter()
+                .map(|package| package.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["hello_world_macro", "serde"]
+        );
+        assert_eq!(
+            lock.packages[0].source.as_deref(),
+            Some("git+https://github.com/HugoByte/aurras.git?branch=next#1f2d3c4")
+        );
+
+        let content = lock.to_string();
+        assert!(content.starts_with("# This file is automatically @generated by composer."));
+        assert_eq!(content.parse::<LockFile>().unwrap(), lock);
+
+        assert_eq!(
+            LockFile::read(Path::new("/nonexistent/composer.lock")).unwrap(),
+            None
+        );
+
+        // A second package using syn 2 with another feature set, and syn 1 through quote
+        let other_lock: LockFile = r#"
+version = 3
+
+[[package]]
+name = "boilerplate_other_0_0_1"
+version = "0.0.1"
+dependencies = [
+ "serde",
+ "syn 2.0.39",
+]
+
+[[package]]
+name = "serde"
+version = "1.0.192"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "bca2a08484b285dcb282d0f67b26cadc0df8b19f8c12502c13d966bf9482f001"
+dependencies = [
+ "serde_derive",
+]
+
+[[package]]
+name = "serde_derive"
+version = "1.0.192"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+dependencies = [
+ "syn 1.0.109",
+]
+
+[[package]]
+name = "syn"
+version = "1.0.109"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "syn"
+version = "2.0.39"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#
+        .parse()
+        .unwrap();
+
+        assert!(lock.pins(&cargo_lock, "boilerplate_hello_0_0_1"));
+        assert!(!lock.pins(&other_lock, "boilerplate_other_0_0_1"));
+
+        lock.merge(&other_lock, "boilerplate_other_0_0_1");
+        assert!(lock.pins(&other_lock, "boilerplate_other_0_0_1"));
+
+        let registry = "registry+https://github.com/rust-lang/crates.io-index";
+        let found: Vec<(&str, &str, Vec<String>)> = lock
+            .packages
+            .iter()
+            .map(|package| {
+                (
+                    package.name.as_str(),
+                    package.version.as_str(),
+                    package.dependencies.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("hello_world_macro", "0.1.0", vec![]),
+                (
+                    "serde",
+                    "1.0.192",
+                    vec![format!("serde_derive 1.0.192 ({registry})")]
+                ),
+                (
+                    "serde_derive",
+                    "1.0.192",
+                    vec![format!("syn 1.0.109 ({registry})")]
+                ),
+                ("syn", "1.0.109", vec![]),
+                ("syn", "2.0.39", vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn manifest_test() {
+        let root =
+            std::env::temp_dir().join(format!("composer-manifest-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("Composer.toml");
+
+        fs::write(
+            &path,
+            "[package]\nname = \"payroll\"\n\n[build]\ntarget = \"wasm32-wasip1\"\nprofile = \"release\"\njobs = 2\n\n[dependencies]\nshared = { path = \"../shared\" }\n\n[lints]\nunused_task = \"deny\"\n\n[env.dev]\nprofile = \"debug\"\noffline = true\n",
+        )
+        .unwrap();
+        let manifest = Manifest::read(&path).unwrap().unwrap();
+        let package = manifest.package.as_ref().unwrap();
+        assert_eq!(package.name, "payroll");
+        assert_eq!(package.entry, vec![PathBuf::from("main.echo")]);
+        assert_eq!(
+            manifest.dependencies["shared"].path,
+            PathBuf::from("../shared")
+        );
+        assert_eq!(manifest.lints["unused_task"], "deny");
+
+        let settings = manifest.settings(None).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("release"));
+        assert_eq!(settings.offline, None);
+
+        let settings = manifest.settings(Some("dev")).unwrap();
+        assert_eq!(settings.profile.as_deref(), Some("debug"));
+        assert_eq!(settings.target.as_deref(), Some("wasm32-wasip1"));
+        assert_eq!(settings.jobs, Some(2));
+        assert_eq!(settings.offline, Some(true));
+
+        assert!(manifest
+            .settings(Some("prod"))
+            .unwrap_err()
+            .to_string()
+            .starts_with("Environment prod is not defined in the manifest, expected one of: dev"));
+
+        fs::write(
+            &path,
+            "[package]\nname = \"payroll\"\nentries = [\"main.echo\"]\n",
+        )
+        .unwrap();
+        let error = Manifest::read(&path).unwrap_err().to_string();
+        assert!(error.contains("unknown field `entries`"));
+
+        fs::write(&path, "[build]\nprofile = \"debug\"\n").unwrap();
+        let error = Manifest::read(&path).unwrap_err().to_string();
+        assert!(error.ends_with("expected a [package] or a [workspace] section"));
+
+        let manifest = Manifest::new("payroll");
+        fs::write(&path, manifest.to_string()).unwrap();
+        assert_eq!(Manifest::read(&path).unwrap(), Some(manifest));
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(Manifest::read(&path).unwrap(), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn compile_workspace_load_test() {
+        let root =
+            std::env::temp_dir().join(format!("composer-workspace-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::create_dir_all(root.join("member")).unwrap();
+        fs::write(
+            root.join("Composer.toml"),
+            "[workspace]\nmembers = [\"member\"]\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("shared/lib.echo"),
+            "def hello(name):\n    return task(kind = \"hello_world\", action_name = name, input_arguments = [])\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("member/main.echo"),
+            "load(\"//shared/lib.echo\", \"hello\")\n\nworkflows(name = \"member\", version = \"0.0.1\", tasks = [hello(\"greet\")])\n",
+        )
+        .unwrap();
+
+        let workspace = SourceFiles::new(Some(root.clone())).unwrap();
+        let members = workspace.members().unwrap().unwrap();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].name(), "member");
+
+        let composer = Composer::default();
+        composer.compile("main.echo", &members[0]).unwrap();
+        assert!(composer.workflows.borrow()[0].tasks.contains_key("greet"));
+
+        // A member found from its own directory still resolves the root of the workspace
+        let member = SourceFiles::new(Some(root.join("member"))).unwrap();
+        assert_eq!(
+            fs::canonicalize(member.root()).unwrap(),
+            fs::canonicalize(&root).unwrap()
+        );
+
+        // An invalid manifest of a parent directory only matters to the members it lists
+        fs::create_dir_all(root.join("other/package")).unwrap();
+        fs::write(root.join("other/Composer.toml"), "[workspace\n").unwrap();
+        let package = SourceFiles::new(Some(root.join("other/package"))).unwrap();
+        assert_eq!(package.root(), package.base());
+
+        fs::write(
+            root.join("Composer.toml"),
+            "[workspace]\nmembers = [\"member\"]\nversion = \"0.0.1\"\n",
+        )
+        .unwrap();
+        let error = SourceFiles::new(Some(root.join("member")))
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Invalid manifest"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn compile_load_test() {
+        let base = std::env::temp_dir().join(format!("composer-load-test-{}", std::process::id()));
+        fs::create_dir_all(base.join("flows/lib")).unwrap();
+
+        // Both flows load the module declaring the workflow, relative to their own directory
+        fs::write(
+            base.join("flows/lib/hello.echo"),
+            "hello = task(kind = \"hello_world\", action_name = \"hello\", input_arguments = [])\n\nworkflows(name = \"hello\", version = \"0.0.1\", tasks = [hello])\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("flows/first.echo"),
+            "load(\"lib/hello.echo\", \"hello\")\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("flows/second.echo"),
+            "load(\"lib/hello.echo\", \"hello\")\n",
+        )
+        .unwrap();
+        fs::write(
+            base.join("main.echo"),
+            "load(\"flows/first.echo\", \"hello\")\nload(\"flows/second.echo\", \"hello\")\n",
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(base.clone())).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        assert_eq!(composer.workflows.borrow().len(), 1);
+
+        fs::write(base.join("a.echo"), "load(\"b.echo\", \"b\")\na = 1\n").unwrap();
+        fs::write(base.join("b.echo"), "load(\"a.echo\", \"a\")\nb = 1\n").unwrap();
+
+        let composer = Composer::default();
+        let error = composer.compile("a.echo", &files).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Load cycle detected: a.echo -> b.echo -> a.echo"
+        );
+
+        fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn compile_library_load_test() {
+        let root =
+            std::env::temp_dir().join(format!("composer-library-test-{}", std::process::id()));
+        fs::create_dir_all(root.join("shared")).unwrap();
+        fs::create_dir_all(root.join("package")).unwrap();
+        fs::write(
+            root.join("shared/openwhisk.echo"),
+            "attributes = {\"api_host\": \"https://localhost\", \"namespace\": \"guest\"}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("package/Composer.toml"),
+            "[package]\nname = \"package\"\n\n[dependencies]\nshared = { path = \"../shared\" }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("package/main.echo"),
+            "load(\"@shared//openwhisk.echo\", \"attributes\")\n\nhello = task(kind = \"openwhisk\", action_name = \"hello\", input_arguments = [], attributes = attributes)\n\nworkflows(name = \"hello\", version = \"0.0.1\", tasks = [hello])\n",
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.join("package"))).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        assert_eq!(
+            composer.workflows.borrow()[0].tasks["hello"].attributes["namespace"],
+            "guest"
+        );
+
+        let error = files.resolve("@missing//openwhisk.echo", None).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Library missing is not a dependency"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    // Runs cargo on a generated package, which takes minutes and may download its
+    // dependencies. Run it with `cargo test -- --ignored`
+    #[test]
+    #[ignore = "runs cargo on a generated package"]
+    fn generated_package_test() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().to_path_buf();
+        fs::write(
+            root.join("main.echo"),
+            r#"attributes = {"api_host": "https://localhost", "namespace": "guest"}
+
+employee_ids = task(
+    kind = "openwhisk",
+    action_name = "employee_ids",
+    input_arguments = [
+        argument(name = "role", input_type = String, one_of = ["admin", "user"]),
+        argument(name = "email", input_type = String, pattern = "^.+@.+$"),
+        argument(name = "limit", input_type = Int, min = 1, max = 100, default_value = 10),
+    ],
+    attributes = attributes,
+    retry = 2,
+    timeout_ms = 5000,
+)
+
+refund = task(kind = "openwhisk", action_name = "refund", input_arguments = [], attributes = attributes)
+
+getaddress = task(
+    kind = "openwhisk",
+    action_name = "getaddress",
+    input_arguments = [argument(name = "id", input_type = Int)],
+    attributes = attributes,
+    operation = Operation.map("address"),
+    depend_on = [depend(task_name = "employee_ids", cur_field = "id", prev_field = "ids")],
+    compensate = refund,
+)
+
+poll = task(
+    kind = "openwhisk",
+    action_name = "poll",
+    input_arguments = [argument(name = "addresses", input_type = HashMap(Int, String))],
+    attributes = attributes,
+    operation = Operation.repeat_until("done", True, 5),
+    depend_on = [depend(task_name = "getaddress", cur_field = "addresses", prev_field = "result")],
+)
+
+workflows(name = "employees", version = "0.0.1", tasks = [employee_ids, getaddress, poll], timeout_ms = 60000)
+"#,
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+
+        // The dependencies are resolved from the local registry cache first, so that the
+        // test also runs without network access
+        let build_path = root.join("build");
+        let mut options = BuildOptions {
+            keep_build: true,
+            offline: true,
+            ..Default::default()
+        };
+
+        let lock = match composer.update_lock_file(&build_path, &options) {
+            Ok(lock) => lock,
+            Err(_) => {
+                options.offline = false;
+                composer.compile("main.echo", &files).unwrap();
+                composer.update_lock_file(&build_path, &options).unwrap()
+            }
+        };
+
+        // The package is built with the merged lock file, as a build pinned by composer.lock
+        let package_dir = build_path.join("employees_0.0.1/boilerplate");
+        fs::write(package_dir.join("Cargo.lock"), lock.to_string()).unwrap();
+        fs::write(
+            package_dir.join("src/tests.rs"),
+            include_str!("../boilerplate/src/tests.rs"),
+        )
+        .unwrap();
+        let mut lib = fs::OpenOptions::new()
+            .append(true)
+            .open(package_dir.join("src/lib.rs"))
+            .unwrap();
+        std::io::Write::write_all(&mut lib, b"\n#[cfg(test)]\nmod tests;\n").unwrap();
+
+        // Compiling the dependencies takes minutes, the target directory is kept between runs.
+        // The tests are compiled without the entry point, which is checked separately
+        for command in ["check", "test"] {
+            let output = Command::new("cargo")
+                .current_dir(&package_dir)
+                .env(
+                    "CARGO_TARGET_DIR",
+                    std::env::temp_dir().join("composer-generated-test-target"),
+                )
+                .args([command, "--quiet"])
+                .args(options.offline.then_some("--offline"))
+                .output()
+                .unwrap();
+
+            assert!(
+                output.status.success(),
+                "cargo {} failed\n{}\n{}",
+                command,
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        let cargo_lock = LockFile::read(&package_dir.join("Cargo.lock"))
+            .unwrap()
+            .unwrap();
+        assert!(lock.pins(&cargo_lock, "boilerplate_employees_0_0_1"));
+    }
+
+    #[test]
+    fn compile_std_load_test() {
+        let root = std::env::temp_dir().join(format!("composer-std-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.echo"),
+            "load(\"@std//openwhisk.echo\", \"action\", \"attributes\")\nload(\"@std//polkadot.echo\", \"staking_payout\")\nload(\"@std//common.echo\", \"arguments\", \"depends\")\n\nsalaries = action(\"salaries\", attributes(\"https://localhost\"), input_arguments = arguments(role = String))\npayout = staking_payout(depend_on = depends(\"salaries\", era = \"era\"))\n\nworkflows(name = \"payout\", version = \"0.0.1\", tasks = [salaries, payout])\n",
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+
+        let workflows = composer.workflows.borrow();
+        assert_eq!(
+            workflows[0].tasks["salaries"].attributes["namespace"],
+            "guest"
+        );
+        assert_eq!(
+            workflows[0].tasks["stakingpayout"].attributes["chain"],
+            "westend"
+        );
+        assert_eq!(
+            workflows[0].tasks["stakingpayout"].depend_on[0].task_name,
+            "salaries"
+        );
+        assert_eq!(
+            workflows[0].tasks["stakingpayout"]
+                .span
+                .as_ref()
+                .map(|span| span.line),
+            Some(6)
+        );
+        drop(workflows);
+
+        fs::write(
+            root.join("main.echo"),
+            "load(\"@std//openwhisk.echo\", \"attributes\")\n\nattributes(\"localhost\")\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("api_host must be an http:// or https:// URL"));
+
+        fs::write(
+            root.join("main.echo"),
+            "load(\"@std//missing.echo\", \"x\")\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Module missing.echo is not part of the standard library"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn compile_span_test() {
+        let root = std::env::temp_dir().join(format!("composer-span-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.echo"),
+            "hello = task(kind = \"hello_world\", action_name = \"hello\", input_arguments = [argument(name = \"name\", input_type = String)])\n\ngreet = task(\n    kind = \"hello_world\",\n    action_name = \"greet\",\n    input_arguments = [argument(name = \"name\", input_type = String)],\n    depend_on = [depend(task_name = \"hello\", cur_field = \"name\", prev_field = \"name\")],\n)\n\nworkflows(name = \"greet\", version = \"0.0.1\", tasks = [greet])\n",
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("Task greet depends on hello, which is not part of the workflow"));
+        assert!(error.contains("main.echo:7:18\n  |\n7 |     depend_on = [depend("));
+
+        fs::write(
+            root.join("main.echo"),
+            "hello = task(kind = \"hello_world\", action_name = \"hello\", input_arguments = [argument(name = \"name\", input_type = String)])\n\nworkflows(name = \"hello\", version = \"0.0.1\", tasks = [hello])\n",
+        )
+        .unwrap();
+
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+
+        let workflows = composer.workflows.borrow();
+        let task = &workflows[0].tasks["hello"];
+        let span = task.span.as_ref().unwrap();
+        assert_eq!((span.line, span.column), (1, 9));
+        assert!(span.file.ends_with("main.echo"));
+
+        let span = task.input_arguments[0].span.as_ref().unwrap();
+        assert_eq!((span.line, span.column), (1, 78));
+        assert_eq!(
+            span.render("Value must be an integer"),
+            format!(
+                "Value must be an integer\n --> {}:1:78\n  |\n1 | {}\n  | {}{}\n  |",
+                span.file,
+                span.source,
+                " ".repeat(77),
+                "^".repeat(44)
+            )
+        );
+        drop(workflows);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn lint_test() {
+        let root = std::env::temp_dir().join(format!("composer-lint-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.echo"),
+            r#"a = task(kind = "openwhisk", action_name = "a", input_arguments = [argument(name = "x", input_type = Int)], attributes = {"api_host": "https://localhost", "namespace": "guest", "insecure": "true"})
+b = task(kind = "hello_world", action_name = "b", input_arguments = [argument(name = "x", input_type = Int, default_value = 1)], depend_on = [depend(task_name = "a", cur_field = "x", prev_field = "x")])
+c = task(kind = "hello_world", action_name = "c", input_arguments = [])
+unused = task(kind = "hello_world", action_name = "unused", input_arguments = [])
+
+allow_lint("insecure_attribute", task = "a")
+
+workflows(name = "first", version = "0.0.1", tasks = [a, b, c])
+workflows(name = "second", version = "0.0.1", tasks = [c])
+"#,
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+
+        let lints = composer.lint(&BTreeMap::new()).unwrap();
+        let found: Vec<(&str, &str)> = lints
+            .iter()
+            .map(|lint| (lint.rule, lint.task.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("unused_task", "unused"),
+                ("unconsumed_output", "b"),
+                ("default_with_depend", "b"),
+                ("duplicate_action", "c"),
+            ]
+        );
+        assert!(lints.iter().all(|lint| lint.severity == Severity::Warn));
+        assert_eq!(lints[0].span.as_ref().map(|span| span.line), Some(4));
+        assert!(lints[1]
+            .to_string()
+            .contains("the workflow returns the outputs of its 2 sink tasks"));
+
+        let severities = BTreeMap::from([
+            ("unused_task".to_string(), "deny".to_string()),
+            ("duplicate_action".to_string(), "allow".to_string()),
+        ]);
+        let lints = composer.lint(&severities).unwrap();
+        assert_eq!(lints.len(), 3);
+        assert_eq!(lints[0].severity, Severity::Deny);
+        assert!(lints[0]
+            .to_string()
+            .starts_with("error[unused_task]: Task unused is never added to a workflow"));
+
+        let severities = BTreeMap::from([("unused".to_string(), "deny".to_string())]);
+        assert!(composer
+            .lint(&severities)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Unknown lint rule unused"));
+        let severities = BTreeMap::from([("unused_task".to_string(), "error".to_string())]);
+        assert!(composer.lint(&severities).is_err());
+
+        fs::write(root.join("main.echo"), "allow_lint(\"unknown\")\n").unwrap();
+        assert!(Composer::default().compile("main.echo", &files).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn kind_attributes_test() {
+        let openwhisk = get_kind_schema("OpenWhisk").unwrap();
+        assert_eq!(openwhisk.name, "openwhisk");
+        assert_eq!(get_kind_crate("Polkadot"), Some("substrate_macro"));
+
+        let attributes = openwhisk
+            .normalize(HashMap::from([
+                (
+                    "API_HOST".to_string(),
+                    serde_json::json!("https://localhost"),
+                ),
+                ("namespace".to_string(), serde_json::json!("guest")),
+                ("Insecure".to_string(), serde_json::json!(true)),
+            ]))
+            .unwrap();
+        assert_eq!(attributes["api_host"], "https://localhost");
+        assert_eq!(attributes["insecure"], "true");
+        assert!(openwhisk.check_required(&attributes).is_ok());
+
+        let attributes = openwhisk.with_defaults(&attributes);
+        assert_eq!(attributes["insecure"], "true");
+        assert_eq!(attributes["auth_key"], "");
+
+        let attributes = openwhisk
+            .normalize(HashMap::from([(
+                "api_host".to_string(),
+                serde_json::json!("https://localhost"),
+            )]))
+            .unwrap();
+        let error = openwhisk.check_required(&attributes).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Kind openwhisk requires the attributes: namespace (string)"
+        );
+
+        let error = openwhisk
+            .normalize(HashMap::from([
+                ("api_host".to_string(), serde_json::json!("localhost")),
+                ("namespace".to_string(), serde_json::json!("guest")),
+            ]))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Attribute api_host of kind openwhisk must be an http:// or https:// URL, got \"localhost\""
+        );
+
+        let error = openwhisk
+            .normalize(HashMap::from([
+                (
+                    "api_host".to_string(),
+                    serde_json::json!("https://localhost"),
+                ),
+                ("namespace".to_string(), serde_json::json!("guest")),
+                (
+                    "apihost".to_string(),
+                    serde_json::json!("https://localhost"),
+                ),
+            ]))
+            .unwrap_err();
+        assert!(error.to_string().starts_with(
+            "Unknown attribute apihost for kind openwhisk, expected one of: api_host"
+        ));
+
+        let polkadot = get_kind_schema("polkadot").unwrap();
+        let attributes = polkadot
+            .normalize(HashMap::from([
+                ("chain".to_string(), serde_json::json!("Westend")),
+                ("operation".to_string(), serde_json::json!("stakingpayout")),
+            ]))
+            .unwrap();
+        assert_eq!(attributes["chain"], "westend");
+
+        let error = polkadot
+            .normalize(HashMap::from([
+                ("chain".to_string(), serde_json::json!("westand")),
+                ("operation".to_string(), serde_json::json!("stakingpayout")),
+            ]))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Attribute chain of kind polkadot must be one of: westend"));
+
+        let root = std::env::temp_dir().join(format!("composer-kind-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.echo"),
+            "hello = task(kind = \"hello\", action_name = \"hello\", input_arguments = [])\n",
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Unknown kind hello, expected one of: openwhisk, polkadot, hello_world"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn task_defaults_test() {
+        let root =
+            std::env::temp_dir().join(format!("composer-defaults-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+
+        fs::write(
+            root.join("main.echo"),
+            "employees = task(kind = \"openwhisk\", action_name = \"employees\", input_arguments = [])\nsalary = task(kind = \"openwhisk\", action_name = \"salary\", input_arguments = [], attributes = {\"namespace\": \"payroll\"}, retry = 0)\n\ndefaults(kind = \"OpenWhisk\", attributes = {\"api_host\": \"https://localhost\", \"namespace\": \"guest\"}, retry = 2)\n\nworkflows(name = \"salary\", version = \"0.0.1\", tasks = [employees, salary])\n",
+        )
+        .unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        let workflow = &composer.workflows.borrow()[0];
+
+        let employees = &workflow.tasks["employees"];
+        assert_eq!(employees.attributes["api_host"], "https://localhost");
+        assert_eq!(employees.attributes["namespace"], "guest");
+        assert_eq!(employees.retry, Some(2));
+
+        let salary = &workflow.tasks["salary"];
+        assert_eq!(salary.attributes["api_host"], "https://localhost");
+        assert_eq!(salary.attributes["namespace"], "payroll");
+        assert_eq!(salary.retry, Some(0));
+
+        let types_rs = generate_types_rs_file_code(workflow, &HashMap::new()).unwrap();
+        assert!(types_rs.contains("workflow.set_task_retries(employees_index, 2);"));
+        assert!(types_rs.contains("workflow.set_task_retries(salary_index, 0);"));
+
+        fs::write(
+            root.join("main.echo"),
+            "employees = task(kind = \"openwhisk\", action_name = \"employees\", input_arguments = [])\n\ndefaults(kind = \"openwhisk\", attributes = {\"api_host\": \"https://localhost\"})\n\nworkflows(name = \"salary\", version = \"0.0.1\", tasks = [employees])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Task employees: Kind openwhisk requires the attributes: namespace (string)"
+        ));
+
+        fs::write(
+            root.join("main.echo"),
+            "defaults(kind = \"openwhisk\", attributes = {\"namespace\": \"guest\"})\ndefaults(kind = \"openwhisk\", retry = 1)\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("The defaults of kind openwhisk are already set"));
+
+        fs::write(
+            root.join("main.echo"),
+            "load(\"@std//openwhisk.echo\", \"action\", \"attributes\")\n\ndefaults(kind = \"openwhisk\", attributes = attributes(\"https://localhost\"))\n\nemployees = action(\"employees\")\n\nworkflows(name = \"salary\", version = \"0.0.1\", tasks = [employees])\n",
+        )
+        .unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        let employees = &composer.workflows.borrow()[0].tasks["employees"];
+        assert_eq!(employees.attributes["api_host"], "https://localhost");
+        assert_eq!(employees.attributes["namespace"], "guest");
+
+        fs::write(
+            root.join("main.echo"),
+            "defaults(kind = \"openwhisk\", attributes = {\"api_host\": \"localhost\"})\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Defaults of kind openwhisk: Attribute api_host of kind openwhisk must be an http:// or https:// URL"
+        ));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn depend_task_test() {
+        let root =
+            std::env::temp_dir().join(format!("composer-depend-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+
+        fs::write(
+            root.join("main.echo"),
+            "load(\"@std//common.echo\", \"depends\")\n\nmodelprice = task(kind = \"hello_world\", action_name = \"modelsprice\", input_arguments = [])\ncar = task(\n    kind = \"hello_world\",\n    action_name = \"car\",\n    input_arguments = [\n        argument(name = \"models\", input_type = List(String)),\n        argument(name = \"price\", input_type = Int),\n        argument(name = \"brand\", input_type = String),\n    ],\n    depend_on = [\n        depend(task = modelprice, cur_field = \"price\", prev_field = \"cost\"),\n        modelprice.output(\"models\"),\n    ] + depends(modelprice, brand = \"make\"),\n)\n\nworkflows(name = \"car\", version = \"0.0.1\", tasks = [modelprice, car])\n",
+        )
+        .unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        let fields: Vec<(String, String, String)> = composer.workflows.borrow()[0].tasks["car"]
+            .depend_on
+            .iter()
+            .map(|depend| {
+                (
+                    depend.task_name.clone(),
+                    depend.cur_field.clone(),
+                    depend.prev_field.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                ("modelsprice", "price", "cost"),
+                ("modelsprice", "models", "models"),
+                ("modelsprice", "brand", "make"),
+            ]
+            .map(|(task, cur, prev)| (
+                task.to_string(),
+                cur.to_string(),
+                prev.to_string()
+            ))
+        );
+
+        fs::write(
+            root.join("main.echo"),
+            "modelprice = task(kind = \"hello_world\", action_name = \"modelsprice\", input_arguments = [])\ncar = task(kind = \"hello_world\", action_name = \"car\", input_arguments = [argument(name = \"price\", input_type = Int)], depend_on = [modelprice.output(\"prices\")])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Task car: dependency field prices is not an input argument of the task, did you mean price?"
+        ));
+
+        fs::write(
+            root.join("main.echo"),
+            "modelprice = task(kind = \"hello_world\", action_name = \"modelsprice\", input_arguments = [])\nprice = depend(task_name = \"modelsprice\", task = modelprice, cur_field = \"price\", prev_field = \"price\")\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("depend() takes either task_name or task, not both"));
+
+        fs::write(
+            root.join("main.echo"),
+            "price = depend(task = \"modelsprice\", cur_field = \"price\", prev_field = \"price\")\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Expected a task returned by task(), got string"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn identifier_test() {
+        assert_eq!(get_identifier("type"), "r#type");
+        assert_eq!(get_identifier("amount"), "amount");
+        assert_eq!(get_task_variable("match"), "r#match");
+
+        assert!(validate_task_name("get-data").is_ok());
+        assert!(validate_task_name("2fast").is_err());
+        assert!(validate_task_name("get data").is_err());
+        assert!(validate_task_name("input").is_err());
+        assert!(validate_task_name("self").is_err());
+        assert!(validate_argument_name("type").is_ok());
+        assert!(validate_argument_name("_id").is_ok());
+        assert!(validate_argument_name("get-data").is_err());
+        assert!(validate_argument_name("1st").is_err());
+        assert!(validate_argument_name("self").is_err());
+
+        let candidates = ["modelsprice".to_string(), "cartype".to_string()];
+        assert_eq!(
+            get_similar_name("modelprice", candidates.iter()),
+            Some(&candidates[0])
+        );
+        assert_eq!(
+            get_similar_name("CarType", candidates.iter()),
+            Some(&candidates[1])
+        );
+        assert_eq!(get_similar_name("salary", candidates.iter()), None);
+
+        let root =
+            std::env::temp_dir().join(format!("composer-identifier-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+
+        fs::write(
+            root.join("main.echo"),
+            "a = task(kind = \"hello_world\", action_name = \"get-data\", input_arguments = [])\nb = task(kind = \"hello_world\", action_name = \"get_data\", input_arguments = [])\n\nworkflows(name = \"data\", version = \"0.0.1\", tasks = [a, b])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Task get_data clashes with task get-data, both generate the identifier GetData"
+        ));
+
+        fs::write(
+            root.join("main.echo"),
+            "a = task(kind = \"hello_world\", action_name = \"a\", input_arguments = [argument(name = \"id\", input_type = Int)])\nb = task(kind = \"hello_world\", action_name = \"b\", input_arguments = [argument(name = \"id\", input_type = Int)])\n\nworkflows(name = \"ids\", version = \"0.0.1\", tasks = [a, b])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Argument id of task b clashes with the argument of task a, both are fields of the input of the workflow"
+        ));
+
+        fs::write(
+            root.join("main.echo"),
+            "a = task(kind = \"hello_world\", action_name = \"a\", input_arguments = [argument(name = \"id\", input_type = Int), argument(name = \"id\", input_type = String)])\n\nworkflows(name = \"ids\", version = \"0.0.1\", tasks = [a])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Argument id is declared more than once in task a"));
+
+        fs::write(
+            root.join("main.echo"),
+            "modelsprice = task(kind = \"hello_world\", action_name = \"modelsprice\", input_arguments = [])\ncar = task(kind = \"hello_world\", action_name = \"car\", input_arguments = [argument(name = \"price\", input_type = Int)], depend_on = [depend(task_name = \"modelprice\", cur_field = \"price\", prev_field = \"price\")])\n\nworkflows(name = \"car\", version = \"0.0.1\", tasks = [modelsprice, car])\n",
+        )
+        .unwrap();
+        let error = Composer::default()
+            .compile("main.echo", &files)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(
+            "Task car depends on modelprice, which is not part of the workflow, did you mean modelsprice?"
+        ));
+
+        fs::write(
+            root.join("main.echo"),
+            "source = task(kind = \"hello_world\", action_name = \"source\", input_arguments = [])\nmatch = task(kind = \"hello_world\", action_name = \"match\", input_arguments = [argument(name = \"type\", input_type = String)], depend_on = [depend(task_name = \"source\", cur_field = \"type\", prev_field = \"kind\")])\n\nworkflows(name = \"match\", version = \"0.0.1\", tasks = [source, match])\n",
+        )
+        .unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+        let types_rs =
+            generate_types_rs_file_code(&composer.workflows.borrow()[0], &HashMap::new()).unwrap();
+        assert!(types_rs.contains("let r#match = Match::new("));
+        assert!(types_rs.contains("Box::new(r#match)"));
+        assert!(types_rs.contains("r#type:\"kind\""));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn build_timeouts_without_clock_test() {
+        let root = std::env::temp_dir().join(format!("composer-clock-test-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(
+            root.join("main.echo"),
+            r#"hello = task(
+    kind = "openwhisk",
+    action_name = "hello",
+    input_arguments = [],
+    attributes = {"api_host": "https://localhost", "namespace": "guest"},
+    timeout_ms = 1000,
+)
+
+workflows(name = "hello", version = "0.0.1", tasks = [hello])
+"#,
+        )
+        .unwrap();
+
+        let files = SourceFiles::new(Some(root.clone())).unwrap();
+        let composer = Composer::default();
+        composer.compile("main.echo", &files).unwrap();
+
+        let options = BuildOptions {
+            cache_dir: root.join("cache"),
+            target: Target::WasmUnknown,
+            ..Default::default()
+        };
+        let reports = composer
+            .build_directory(&root.join("build"), &root, &options, |_, _, _| {})
+            .unwrap();
+
+        assert_eq!(reports[0].stage, BuildStage::Failed);
+        assert_eq!(
+            reports[0].error.as_ref().unwrap().to_string(),
+            "hello: Timeouts are not supported on wasm32-unknown-unknown, which provides no clock, build for wasm32-wasip1 instead"
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn build_options_jobs_test() {
+        let options = BuildOptions::default();
+        assert!(options.jobs() >= 1);
+
+        let options = BuildOptions {
+            jobs: 3,
+            ..Default::default()
+        };
+        assert_eq!(options.jobs(), 3);
+        assert_eq!(BuildStage::Compiling.to_string(), "compiling");
+    }
+
+    #[test]
+    fn build_options_target_profile_test() {
+        assert_eq!("wasm32-wasip1".parse(), Ok(Target::Wasip1));
+        assert_eq!("native".parse(), Ok(Target::Native));
+        assert!("wasm32-wasi".parse::<Target>().is_err());
+        assert_eq!("dev".parse(), Ok(Profile::Debug));
+        assert!("bench".parse::<Profile>().is_err());
+
+        let options = BuildOptions {
+            cache_dir: PathBuf::from("/cache"),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.built_artifact("boilerplate_hello_0_0_1"),
+            PathBuf::from("/cache/target/wasm32-wasip1/release/boilerplate_hello_0_0_1.wasm")
+        );
+        assert_eq!(options.artifact_key(), "wasm32-wasip1/release");
+        assert!(options.cargo_profile().contains("[profile.release]"));
+        assert!(options.cargo_profile().contains("opt-level = 3\n"));
+
+        let options = BuildOptions {
+            cache_dir: PathBuf::from("/cache"),
+            target: Target::WasmUnknown,
+            profile: Profile::Debug,
+            optimize_size: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            options.built_artifact("boilerplate_hello_0_0_1"),
+            PathBuf::from(
+                "/cache/target/wasm32-unknown-unknown/debug/boilerplate_hello_0_0_1.wasm"
+            )
+        );
+        assert_eq!(
+            options.artifact_key(),
+            "wasm32-unknown-unknown/debug/optimized"
+        );
+        assert_eq!(
+            options.cargo_profile(),
+            "[profile.dev]\nopt-level = \"z\"\n"
+        );
+        assert_eq!(
+            options.cached_wasm("0123"),
+            PathBuf::from("/cache/wasm/0123.wasm")
+        );
+        assert_eq!(
+            options.cached_lock("0123"),
+            PathBuf::from("/cache/wasm/0123.lock")
+        );
+    }
+
+    #[test]
+    fn get_flow_is_sorted_test() {
+        let mut tasks = HashMap::new();
+
+        for name in ["delta", "alpha", "charlie", "bravo"] {
+            tasks.insert(
+                name.to_string(),
+                Task {
+                    action_name: name.to_string(),
+                    ..Default::default()
+                },
+            );
+        }
+
+        let workflow = Workflow {
+            name: "test-workflow".to_string(),
+            version: "0.0.1".to_string(),
+            tasks,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            workflow.get_flow(),
+            vec!["alpha", "bravo", "charlie", "delta"]
+        );
+    }
+
     #[test]
     fn get_attributes_test() {
         let composer = Composer::default();
@@ -180,7 +1870,12 @@ mod tests {
         );
 
         composer
-            .add_workflow("test-workflow".to_string(), "0.0.1".to_string(), tasks)
+            .add_workflow(
+                "test-workflow".to_string(),
+                "0.0.1".to_string(),
+                tasks,
+                None,
+            )
             .unwrap();
 
         let composer_task = &composer.workflows.borrow()[0].tasks;
@@ -0,0 +1,138 @@
+use super::*;
+use regex::Regex;
+
+/// Rule an input argument of the workflow has to satisfy.
+/// Numbers and the allowed values are kept as JSON strings, so that the constraint can be
+/// compared and stored along with the rest of the workflow.
+#[derive(Debug, PartialEq, Eq, Allocative, Clone, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Constraint {
+    Min(String),
+    Max(String),
+    Pattern(String),
+    OneOf(Vec<String>),
+    MinLen(usize),
+    NonEmpty,
+}
+
+impl Constraint {
+    /// Returns the name of the rule as it is written in the config file
+    pub fn name(&self) -> &'static str {
+        match self {
+            Constraint::Min(_) => "min",
+            Constraint::Max(_) => "max",
+            Constraint::Pattern(_) => "pattern",
+            Constraint::OneOf(_) => "one_of",
+            Constraint::MinLen(_) => "min_len",
+            Constraint::NonEmpty => "non_empty",
+        }
+    }
+
+    /// Checks whether the rule can be applied to an argument of the given type.
+    /// This method is invoked by the argument function inside the starlark_module.
+    ///
+    /// # Arguments
+    ///
+    /// * `input_type` - A reference to the type of the argument
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success if the rule applies to the type, or an
+    ///   error if it does not or if the pattern is not a valid regular expression
+    ///
+    pub fn validate_type(&self, input_type: &RustType) -> Result<(), Error> {
+        let applies = match self {
+            Constraint::Min(_) | Constraint::Max(_) => {
+                matches!(input_type, RustType::Int | RustType::Uint | RustType::Float)
+            }
+            Constraint::Pattern(pattern) => {
+                Regex::new(pattern)
+                    .map_err(|err| Error::msg(format!("Invalid pattern {:?}: {}", pattern, err)))?;
+
+                *input_type == RustType::String
+            }
+            Constraint::OneOf(_) => matches!(
+                input_type,
+                RustType::Int | RustType::Uint | RustType::Float | RustType::String
+            ),
+            Constraint::MinLen(_) | Constraint::NonEmpty => matches!(
+                input_type,
+                RustType::String | RustType::List(_) | RustType::HashMap(_, _)
+            ),
+        };
+
+        if !applies {
+            return Err(Error::msg(format!(
+                "Constraint {} cannot be applied to an argument of type {}",
+                self.name(),
+                input_type
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks the given value against the rule
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - A reference to the JSON value to be checked
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), String>` - Result indicating success if the value satisfies the rule, or
+    ///   an error message describing the violation
+    ///
+    pub fn check(&self, value: &serde_json::Value) -> Result<(), String> {
+        let satisfied = match self {
+            Constraint::Min(min) => {
+                value.as_f64().unwrap_or(f64::NAN) >= min.parse::<f64>().unwrap_or(f64::NAN)
+            }
+            Constraint::Max(max) => {
+                value.as_f64().unwrap_or(f64::NAN) <= max.parse::<f64>().unwrap_or(f64::NAN)
+            }
+            Constraint::Pattern(pattern) => match (Regex::new(pattern), value.as_str()) {
+                (Ok(regex), Some(value)) => regex.is_match(value),
+                _ => false,
+            },
+            // Numbers are compared by value, as in the generated packages
+            Constraint::OneOf(values) => values
+                .iter()
+                .filter_map(|allowed| serde_json::from_str::<serde_json::Value>(allowed).ok())
+                .any(|allowed| match (allowed.as_f64(), value.as_f64()) {
+                    (Some(allowed), Some(value)) => allowed == value,
+                    _ => &allowed == value,
+                }),
+            Constraint::MinLen(min_len) => get_length(value) >= *min_len,
+            Constraint::NonEmpty => get_length(value) > 0,
+        };
+
+        if satisfied {
+            Ok(())
+        } else {
+            Err(format!("value {} does not satisfy {}", value, self))
+        }
+    }
+}
+
+impl Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Min(min) => write!(f, "min = {min}"),
+            Constraint::Max(max) => write!(f, "max = {max}"),
+            Constraint::Pattern(pattern) => write!(f, "pattern = {pattern:?}"),
+            Constraint::OneOf(values) => write!(f, "one_of = [{}]", values.join(", ")),
+            Constraint::MinLen(min_len) => write!(f, "min_len = {min_len}"),
+            Constraint::NonEmpty => write!(f, "non_empty"),
+        }
+    }
+}
+
+fn get_length(value: &serde_json::Value) -> usize {
+    match value {
+        serde_json::Value::String(value) => value.chars().count(),
+        serde_json::Value::Array(value) => value.len(),
+        serde_json::Value::Object(value) => value.len(),
+        _ => 0,
+    }
+}
@@ -10,4 +10,10 @@ pub struct Input {
     pub default_value: Option<String>,
     #[serde(default)]
     pub is_depend: bool,
+    /// Rules the value of the argument has to satisfy
+    #[serde(default)]
+    pub constraints: Vec<Constraint>,
+    /// Call of the config file which created the argument
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
 }
@@ -0,0 +1,298 @@
+use super::*;
+
+/// Type of the value of an attribute of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    String,
+    /// An `http://` or `https://` URL
+    Url,
+    /// `true` or `false`, given as a boolean or as a string
+    Bool,
+    /// One of the given values
+    Enum(&'static [&'static str]),
+}
+
+impl Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeType::String => write!(f, "string"),
+            AttributeType::Url => write!(f, "URL"),
+            AttributeType::Bool => write!(f, "bool"),
+            AttributeType::Enum(values) => write!(f, "one of: {}", values.join(", ")),
+        }
+    }
+}
+
+/// Attribute a kind accepts on its tasks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttributeSchema {
+    pub name: &'static str,
+    pub attribute_type: AttributeType,
+    pub required: bool,
+    /// Value the derive macro of the kind is given when the attribute is not set
+    pub default: Option<&'static str>,
+}
+
+/// Kind of task, along with the derive macro implementing it and the attributes its tasks
+/// accept
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KindSchema {
+    pub name: &'static str,
+    /// Derive macro generating the code of the tasks of the kind
+    pub derive: &'static str,
+    /// Crate providing the derive macro
+    pub crate_name: &'static str,
+    pub attributes: &'static [AttributeSchema],
+}
+
+/// Chains the polkadot kind can connect to
+pub const POLKADOT_CHAINS: &[&str] = &["westend", "polkadot", "kusama", "rococo"];
+
+/// Operations the polkadot kind implements
+pub const POLKADOT_OPERATIONS: &[&str] = &["stakingpayout"];
+
+/// Kinds of task supported by the composer
+pub const KINDS: &[KindSchema] = &[
+    KindSchema {
+        name: "openwhisk",
+        derive: "OpenWhisk",
+        crate_name: "openwhisk_macro",
+        attributes: &[
+            AttributeSchema {
+                name: "api_host",
+                attribute_type: AttributeType::Url,
+                required: true,
+                default: None,
+            },
+            AttributeSchema {
+                name: "namespace",
+                attribute_type: AttributeType::String,
+                required: true,
+                default: None,
+            },
+            AttributeSchema {
+                name: "auth_key",
+                attribute_type: AttributeType::String,
+                required: false,
+                default: Some(""),
+            },
+            AttributeSchema {
+                name: "insecure",
+                attribute_type: AttributeType::Bool,
+                required: false,
+                default: Some("false"),
+            },
+        ],
+    },
+    KindSchema {
+        name: "polkadot",
+        derive: "Polkadot",
+        crate_name: "substrate_macro",
+        attributes: &[
+            AttributeSchema {
+                name: "chain",
+                attribute_type: AttributeType::Enum(POLKADOT_CHAINS),
+                required: true,
+                default: None,
+            },
+            AttributeSchema {
+                name: "operation",
+                attribute_type: AttributeType::Enum(POLKADOT_OPERATIONS),
+                required: true,
+                default: None,
+            },
+        ],
+    },
+    KindSchema {
+        name: "hello_world",
+        derive: "HelloWorldDerive",
+        crate_name: "hello_world_macro",
+        attributes: &[],
+    },
+];
+
+/// Returns the schema of the given kind, whose name is compared case-insensitively
+///
+/// # Arguments
+///
+/// * `kind` - A string slice that holds the name of the kind
+///
+/// # Returns
+///
+/// * `Option<&KindSchema>` - An option containing the schema, or None if the kind is unknown
+///
+pub fn get_kind_schema(kind: &str) -> Option<&'static KindSchema> {
+    KINDS
+        .iter()
+        .find(|schema| schema.name.eq_ignore_ascii_case(kind))
+}
+
+impl KindSchema {
+    /// Checks the attributes given in the config file against the schema without requiring
+    /// the required ones, which may still come from the defaults of the kind.
+    /// This method is invoked by the task and defaults functions inside the starlark_module.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - The attributes given in the config file, by name
+    ///
+    /// # Returns
+    ///
+    /// * `Result<HashMap<String, String>, Error>` - Result containing the attributes by the
+    ///   name the kind declares, or an error if an attribute is unknown or has a value of the
+    ///   wrong type
+    ///
+    pub fn normalize(
+        &self,
+        attributes: HashMap<String, serde_json::Value>,
+    ) -> Result<HashMap<String, String>, Error> {
+        let mut validated = HashMap::new();
+        let mut names: Vec<&String> = attributes.keys().collect();
+        names.sort();
+
+        for name in names {
+            let schema = self
+                .attributes
+                .iter()
+                .find(|schema| schema.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    let expected = match self.attributes.is_empty() {
+                        true => "it takes no attributes".to_string(),
+                        false => format!(
+                            "expected one of: {}",
+                            self.attributes
+                                .iter()
+                                .map(|schema| schema.name)
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    };
+
+                    Error::msg(format!(
+                        "Unknown attribute {} for kind {}, {}",
+                        name, self.name, expected
+                    ))
+                })?;
+
+            if validated.contains_key(schema.name) {
+                return Err(Error::msg(format!(
+                    "Attribute {} of kind {} is given more than once",
+                    schema.name, self.name
+                )));
+            }
+
+            let value = schema.check(&attributes[name]).map_err(|err| {
+                Error::msg(format!(
+                    "Attribute {} of kind {} {}",
+                    schema.name, self.name, err
+                ))
+            })?;
+
+            validated.insert(schema.name.to_string(), value);
+        }
+
+        Ok(validated)
+    }
+
+    /// Checks that the normalized attributes of a task hold every attribute the kind requires.
+    /// This method is invoked by the workflows function inside the starlark_module, once the
+    /// defaults of the kind are merged into the task.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - A reference to the attributes returned by `normalize`
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success, or an error listing the missing
+    ///   attributes
+    ///
+    pub fn check_required(&self, attributes: &HashMap<String, String>) -> Result<(), Error> {
+        let missing: Vec<String> = self
+            .attributes
+            .iter()
+            .filter(|schema| schema.required && !attributes.contains_key(schema.name))
+            .map(|schema| format!("{} ({})", schema.name, schema.attribute_type))
+            .collect();
+
+        if !missing.is_empty() {
+            return Err(Error::msg(format!(
+                "Kind {} requires the attributes: {}",
+                self.name,
+                missing.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the attributes of a task completed with the defaults of the attributes it does
+    /// not set, as the derive macro of the kind expects every attribute it reads to be given
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - A reference to the attributes returned by `normalize`
+    ///
+    pub fn with_defaults(&self, attributes: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut completed = attributes.clone();
+
+        for schema in self.attributes {
+            if let Some(default) = schema.default {
+                completed
+                    .entry(schema.name.to_string())
+                    .or_insert_with(|| default.to_string());
+            }
+        }
+
+        completed
+    }
+}
+
+impl AttributeSchema {
+    /// Returns the value of the attribute as it is passed to the derive macro of the kind, or
+    /// an error describing the expected value
+    fn check(&self, value: &serde_json::Value) -> Result<String, Error> {
+        let text = match value {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Bool(value) if self.attribute_type == AttributeType::Bool => {
+                value.to_string()
+            }
+            _ => return Err(Error::msg(format!("must be a string, got {}", value))),
+        };
+
+        match self.attribute_type {
+            AttributeType::String => Ok(text),
+            AttributeType::Url => {
+                let host = text
+                    .strip_prefix("https://")
+                    .or_else(|| text.strip_prefix("http://"));
+
+                match host {
+                    Some(host) if !host.is_empty() && !host.contains(char::is_whitespace) => {
+                        Ok(text)
+                    }
+                    _ => Err(Error::msg(format!(
+                        "must be an http:// or https:// URL, got {:?}",
+                        text
+                    ))),
+                }
+            }
+            AttributeType::Bool => match text.to_lowercase().as_str() {
+                "true" => Ok("true".to_string()),
+                "false" => Ok("false".to_string()),
+                _ => Err(Error::msg(format!("must be true or false, got {:?}", text))),
+            },
+            AttributeType::Enum(values) => values
+                .iter()
+                .find(|value| value.eq_ignore_ascii_case(&text))
+                .map(|value| value.to_string())
+                .ok_or_else(|| {
+                    Error::msg(format!(
+                        "must be one of: {}, got {:?}",
+                        values.join(", "),
+                        text
+                    ))
+                }),
+        }
+    }
+}
@@ -7,6 +7,25 @@ macro_rules! impl_starlark_values {
         #[starlark_value(type = stringify!($typ) )]
         impl<'v> StarlarkValue<'v> for $typ {}
 
+        impl_starlark_values!(@display $typ);
+    };
+    ($typ: ident, $methods: ident) => {
+        starlark_simple_value!($typ);
+
+        #[starlark_value(type = stringify!($typ) )]
+        impl<'v> StarlarkValue<'v> for $typ {
+            fn get_methods() -> Option<&'static Methods>
+            where
+                Self: Sized,
+            {
+                static RES: MethodsStatic = MethodsStatic::new();
+                RES.methods($methods)
+            }
+        }
+
+        impl_starlark_values!(@display $typ);
+    };
+    (@display $typ: ident) => {
         impl Display for $typ {
             fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
                 write!(f, "{:?}", self)
@@ -15,18 +34,24 @@ macro_rules! impl_starlark_values {
     };
 }
 
+mod constraint;
 mod input;
+mod kind;
 mod rust_types;
+mod span;
 mod task;
 mod workflow;
 
+pub use constraint::*;
 pub use input::*;
+pub use kind::*;
 pub use rust_types::*;
+pub use span::*;
 pub use task::*;
 pub use workflow::*;
 
 impl_starlark_values!(Depend);
-impl_starlark_values!(Task);
+impl_starlark_values!(Task, starlark_task_methods);
 impl_starlark_values!(Operation);
 impl_starlark_values!(Input);
 impl_starlark_values!(Workflow);
@@ -1,7 +1,10 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize)]
+#[derive(
+    Debug, Default, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize,
+)]
 pub enum RustType {
+    #[default]
     Null,
     Int,
     Uint,
@@ -15,12 +18,6 @@ pub enum RustType {
     Struct(String),
 }
 
-impl Default for RustType {
-    fn default() -> RustType {
-        Self::Null
-    }
-}
-
 starlark_simple_value!(RustType);
 
 #[starlark_value(type = "RustType")]
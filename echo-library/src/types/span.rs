@@ -0,0 +1,98 @@
+use super::*;
+use starlark::codemap::FileSpan;
+
+/// Location of the call in a config file which created a task, an argument or a dependency,
+/// so that the errors found once the config file is evaluated can point at it
+#[derive(Debug, Default, PartialEq, Eq, Allocative, Clone, Deserialize, Serialize)]
+pub struct Span {
+    pub file: String,
+    /// Line of the call, starting at 1
+    pub line: usize,
+    /// Column of the call in characters, starting at 1
+    pub column: usize,
+    /// Number of characters of the call on its first line
+    pub length: usize,
+    /// First line of the call
+    pub source: String,
+}
+
+impl Span {
+    /// Renders the given message along with the location and the source of the span, the
+    /// way starlark renders its own evaluation errors
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The message to render
+    ///
+    /// # Returns
+    ///
+    /// * `String` - The message followed by the location and an annotated snippet of the source
+    ///
+    pub fn render(&self, message: impl Display) -> String {
+        let number = self.line.to_string();
+        let margin = " ".repeat(number.len());
+
+        format!(
+            "{}\n{}--> {}\n{} |\n{} | {}\n{} | {}{}\n{} |",
+            message,
+            margin,
+            self,
+            margin,
+            number,
+            self.source,
+            margin,
+            " ".repeat(self.column.saturating_sub(1)),
+            "^".repeat(self.length.max(1)),
+            margin
+        )
+    }
+}
+
+impl From<FileSpan> for Span {
+    fn from(span: FileSpan) -> Self {
+        let resolved = span.resolve_span();
+        let source = span
+            .file
+            .source_line(resolved.begin.line)
+            .trim_end()
+            .to_string();
+
+        let end = if resolved.end.line == resolved.begin.line {
+            resolved.end.column
+        } else {
+            source.chars().count()
+        };
+
+        Span {
+            file: span.filename().to_string(),
+            line: resolved.begin.line + 1,
+            column: resolved.begin.column + 1,
+            length: end.saturating_sub(resolved.begin.column),
+            source,
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+/// Renders the message with the given span if there is one
+///
+/// # Arguments
+///
+/// * `message` - The message to render
+/// * `span` - An optional reference to the span the message is about
+///
+/// # Returns
+///
+/// * `String` - The rendered message, or the message alone without a span
+///
+pub fn with_span(message: impl Display, span: Option<&Span>) -> String {
+    match span {
+        Some(span) => span.render(message),
+        None => message.to_string(),
+    }
+}
@@ -1,11 +1,15 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize)]
+#[derive(
+    Debug, Default, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize,
+)]
 pub enum Operation {
+    #[default]
     Normal,
     Concat,
     Combine,
     Map(String),
+    RepeatUntil(Repeat),
 }
 
 impl Operation {
@@ -16,14 +20,28 @@ impl Operation {
     pub fn is_combine(&self) -> bool {
         matches!(self, Self::Combine)
     }
-}
 
-impl Default for Operation {
-    fn default() -> Operation {
-        Self::Normal
+    pub fn is_repeat(&self) -> bool {
+        matches!(self, Self::RepeatUntil(_))
     }
 }
 
+/// Condition and bound of a task that is executed repeatedly until a field of its own
+/// output holds the expected value
+#[derive(
+    Debug, Default, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize,
+)]
+pub struct Repeat {
+    /// Output field checked after every iteration
+    pub field: String,
+    /// JSON value the output field must be equal to for the loop to stop
+    pub value: String,
+    pub max_iterations: u32,
+    /// Input fields of the task mapped to the output fields of the previous iteration
+    #[serde(default)]
+    pub feedback: HashMap<String, String>,
+}
+
 #[derive(
     Debug, Default, PartialEq, Eq, Allocative, ProvidesStaticType, Clone, Deserialize, Serialize,
 )]
@@ -31,6 +49,9 @@ pub struct Depend {
     pub task_name: String,
     pub cur_field: String,
     pub prev_field: String,
+    /// Call of the config file which created the dependency
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
 }
 
 #[derive(
@@ -44,4 +65,49 @@ pub struct Task {
     #[serde(default)]
     pub operation: Operation,
     pub depend_on: Vec<Depend>,
+    /// Task executed to undo the effects of this task when a later task of the workflow fails
+    #[serde(default)]
+    pub compensate: Option<Box<Task>>,
+    /// Maximum time in milliseconds the task may run
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+    /// Number of times the task is run again when it fails with a retryable error
+    #[serde(default)]
+    pub retry: Option<u32>,
+    /// Call of the config file which created the task
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub span: Option<Span>,
+}
+
+/// Values given by `defaults()` to every task of a kind, unless the task sets its own
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct TaskDefaults {
+    pub attributes: HashMap<String, String>,
+    pub retry: Option<u32>,
+}
+
+impl Task {
+    /// Merges the defaults of the kind of the task, and of the kinds of its compensation
+    /// tasks, into the values the task leaves unset. Attributes set on the task override the
+    /// default attributes one by one.
+    ///
+    /// # Arguments
+    ///
+    /// * `defaults` - A reference to the defaults by kind
+    ///
+    pub fn apply_defaults(&mut self, defaults: &HashMap<String, TaskDefaults>) {
+        if let Some(defaults) = defaults.get(&self.kind) {
+            for (name, value) in defaults.attributes.iter() {
+                self.attributes
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+
+            self.retry = self.retry.or(defaults.retry);
+        }
+
+        if let Some(compensate) = self.compensate.as_mut() {
+            compensate.apply_defaults(defaults);
+        }
+    }
 }
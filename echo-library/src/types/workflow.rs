@@ -1,13 +1,236 @@
 use super::*;
 
-#[derive(Debug, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize)]
+#[derive(
+    Debug, Default, PartialEq, Eq, ProvidesStaticType, Allocative, Clone, Deserialize, Serialize,
+)]
 pub struct Workflow {
     pub name: String,
     pub version: String,
     pub tasks: HashMap<String, Task>,
+    /// Maximum time in milliseconds the whole workflow may run
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
 }
 
 impl Workflow {
+    /// Validates the tasks of the workflow.
+    /// This method is invoked by the workflows function inside the starlark_module.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<(), Error>` - Result indicating success if the workflow is valid, or an error
+    ///   if a task depends on a task outside of the workflow, if two tasks have the same name,
+    ///   if the dependencies of the tasks form a cycle or if a repeated task has an invalid
+    ///   loop definition
+    ///
+    pub fn validate(&self) -> Result<(), Error> {
+        for (task_name, task) in self.tasks.iter() {
+            for depend in task.depend_on.iter() {
+                if !self.tasks.contains_key(&depend.task_name) {
+                    return Err(Error::msg(with_span(
+                        format!(
+                            "Task {} depends on {}, which is not part of the workflow{}",
+                            task_name,
+                            depend.task_name,
+                            get_similar_name(&depend.task_name, self.tasks.keys())
+                                .map(|name| format!(", did you mean {}?", name))
+                                .unwrap_or_default()
+                        ),
+                        depend.span.as_ref().or(task.span.as_ref()),
+                    )));
+                }
+            }
+
+            if let Some(compensation) = &task.compensate {
+                if self.tasks.contains_key(&compensation.action_name) {
+                    return Err(Error::msg(with_span(
+                        format!(
+                            "Task {}: compensation task {} must not be a task of the workflow",
+                            task_name, compensation.action_name
+                        ),
+                        compensation.span.as_ref(),
+                    )));
+                }
+
+                if compensation.compensate.is_some() {
+                    return Err(Error::msg(with_span(
+                        format!(
+                            "Task {}: compensation task {} must not have a compensation",
+                            task_name, compensation.action_name
+                        ),
+                        compensation.span.as_ref(),
+                    )));
+                }
+
+                for depend in compensation.depend_on.iter() {
+                    if &depend.task_name != task_name {
+                        return Err(Error::msg(with_span(
+                            format!(
+                                "Task {}: compensation task {} can only depend on the task it compensates",
+                                task_name, compensation.action_name
+                            ),
+                            depend.span.as_ref().or(compensation.span.as_ref()),
+                        )));
+                    }
+                }
+            }
+
+            if let Operation::RepeatUntil(repeat) = &task.operation {
+                if repeat.max_iterations == 0 {
+                    return Err(Error::msg(with_span(
+                        format!(
+                            "Task {}: max_iterations of the loop must be greater than zero",
+                            task_name
+                        ),
+                        task.span.as_ref(),
+                    )));
+                }
+
+                for field in repeat.feedback.keys() {
+                    if !task
+                        .input_arguments
+                        .iter()
+                        .any(|argument| &argument.name == field)
+                    {
+                        return Err(Error::msg(with_span(
+                            format!(
+                                "Task {}: loop feedback field {} is not an input argument of the task",
+                                task_name, field
+                            ),
+                            task.span.as_ref(),
+                        )));
+                    }
+                }
+            }
+        }
+
+        self.with_compensations()?;
+
+        if let Some(cycle) = self.find_cycle() {
+            return Err(Error::msg(with_span(
+                format!(
+                    "Tasks {} depend on each other in a cycle",
+                    cycle.join(" -> ")
+                ),
+                self.tasks[&cycle[0]].span.as_ref(),
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Finds a cycle in the dependencies of the tasks, which can not be scheduled since
+    /// every task of the cycle waits for the output of another one.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<Vec<String>>` - The names of the tasks of the first cycle found, starting and
+    ///   ending with the same task, or None if the dependencies form a DAG
+    ///
+    fn find_cycle(&self) -> Option<Vec<String>> {
+        fn visit(
+            workflow: &Workflow,
+            task_name: &String,
+            done: &mut HashSet<String>,
+            path: &mut Vec<String>,
+        ) -> Option<Vec<String>> {
+            if let Some(start) = path.iter().position(|name| name == task_name) {
+                let mut cycle = path[start..].to_vec();
+                cycle.push(task_name.clone());
+                return Some(cycle);
+            }
+
+            if done.contains(task_name) {
+                return None;
+            }
+
+            path.push(task_name.clone());
+
+            for depend in workflow.tasks[task_name].depend_on.iter() {
+                if let Some(cycle) = visit(workflow, &depend.task_name, done, path) {
+                    return Some(cycle);
+                }
+            }
+
+            path.pop();
+            done.insert(task_name.clone());
+            None
+        }
+
+        let mut done = HashSet::new();
+
+        self.sorted_tasks()
+            .into_iter()
+            .find_map(|(task_name, _)| visit(self, task_name, &mut done, &mut Vec::new()))
+    }
+
+    /// Returns a copy of the workflow with the compensation tasks added to its tasks, so that
+    /// the types of the compensation tasks are generated along with the other tasks.
+    ///
+    /// # Returns
+    ///
+    /// * `Result<Workflow, Error>` - A workflow containing the tasks and their compensation
+    ///   tasks, or an error if a compensation task has the name of a task of the workflow or of
+    ///   a different compensation task
+    ///
+    pub fn with_compensations(&self) -> Result<Workflow, Error> {
+        let mut workflow = self.clone();
+
+        for (task_name, task) in self.sorted_tasks() {
+            if let Some(compensation) = &task.compensate {
+                // A compensation task shared by several tasks is only added once
+                let duplicate = match workflow.tasks.get(&compensation.action_name) {
+                    Some(existing) => {
+                        self.tasks.contains_key(&compensation.action_name)
+                            || existing != compensation.as_ref()
+                    }
+                    None => false,
+                };
+
+                if duplicate {
+                    return Err(Error::msg(with_span(
+                        format!(
+                            "Task {}: compensation task {} has the name of another task of \
+                            workflow {}",
+                            task_name, compensation.action_name, self.name
+                        ),
+                        compensation.span.as_ref(),
+                    )));
+                }
+
+                workflow
+                    .tasks
+                    .insert(compensation.action_name.clone(), *compensation.clone());
+            }
+        }
+
+        Ok(workflow)
+    }
+
+    /// Returns whether the workflow or one of its tasks has a timeout, which the generated
+    /// package checks against the clock of the platform.
+    ///
+    /// # Returns
+    ///
+    /// * `bool` - True if a timeout is set on the workflow or on one of its tasks
+    ///
+    pub fn has_timeouts(&self) -> bool {
+        self.timeout_ms.is_some() || self.tasks.values().any(|task| task.timeout_ms.is_some())
+    }
+
+    /// Returns the tasks of the workflow ordered by their name, so that the code generated for
+    /// the workflow is the same on every build.
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<(&String, &Task)>` - A vector containing the names and the tasks
+    ///
+    pub fn sorted_tasks(&self) -> Vec<(&String, &Task)> {
+        let mut tasks: Vec<(&String, &Task)> = self.tasks.iter().collect();
+        tasks.sort_by(|a, b| a.0.cmp(b.0));
+        tasks
+    }
+
     /// Finds the list of dependencies that the given task depends on.
     ///
     /// # Arguments
@@ -31,6 +254,31 @@ impl Workflow {
         Some(dependencies)
     }
 
+    /// Finds the tasks whose output is not consumed by any other task of the workflow.
+    /// The outputs of these tasks are the result of the workflow.
+    ///
+    /// # Arguments
+    ///
+    /// * `flow` - A slice of task names in the order of the topological sort
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<String>` - A vector containing the names of the sink tasks in the order of
+    ///   the given flow
+    ///
+    pub fn get_sinks(&self, flow: &[String]) -> Vec<String> {
+        let consumed: HashSet<&String> = self
+            .tasks
+            .values()
+            .flat_map(|task| task.depend_on.iter().map(|depend| &depend.task_name))
+            .collect();
+
+        flow.iter()
+            .filter(|task_name| !consumed.contains(task_name))
+            .cloned()
+            .collect()
+    }
+
     /// Performs depth-first search (DFS) in the workflow subgraph.
     /// This method is invoked within the get_flow method to perform `Topological-Sorting`
     /// # Arguments
@@ -76,7 +324,7 @@ impl Workflow {
             visited.insert(task.0.to_string(), false);
         }
 
-        for task in self.tasks.iter() {
+        for task in self.sorted_tasks() {
             if !visited[task.0] {
                 self.dfs(task.0, &mut visited, &mut flow)
             }